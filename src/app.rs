@@ -14,10 +14,18 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
+use std::time::Duration;
+
 use rand::seq::SliceRandom;
 use rand::{rng, rngs::ThreadRng, RngCore};
 
 use ratatui::widgets::ListState;
+use serde::{Deserialize, Serialize};
+
+use crate::db::TOTAL_COUNT_TRIES_PER_QUESTION;
+use crate::selection::{Filter, Selection, DEFAULT_MISTAKE_DAYS};
+use crate::stats::SessionStats;
+use crate::textinput::TextInput;
 
 /// This struct contains the programs state.
 ///
@@ -27,8 +35,11 @@ use ratatui::widgets::ListState;
 ///
 /// [^note]: As else this progress count would need to be querried in the [db](crate::db::DB::get_total_progress), every frame.
 ///
+/// The TUI is only redrawn after input. If something on screen changes on its own (e.g. a countdown),
+/// `tick_rate` has to be set, so that the main loop wakes up regularly.
+///
 /// ```
-/// let first_question = QuestionAnswer::new(0, "What is 1+1?", vec!["3", "2", "1", "4"], 1);
+/// let first_question = QuestionAnswer::new(0, "What is 1+1?", vec!["3", "2", "1", "4"], vec![1]);
 /// let mut app = App::new(
 ///       first_question,
 ///       0,
@@ -39,11 +50,37 @@ use ratatui::widgets::ListState;
 #[derive(Default, Debug, Clone)]
 pub struct App {
     pub exit: bool,
+    /// Set if every question of the selection is learned, so there is no session to resume.
+    pub finished: bool,
     pub item_list_state: ListState,
     pub question_answer: QuestionAnswer,
     pub total_progress: usize,
     pub total_question_count: usize,
     pub rng: ThreadRng,
+    pub tick_rate: Option<Duration>,
+    pub scoring: Scoring,
+    pub answer_mode: AnswerMode,
+    /// Answer typed by the user in [AnswerMode::FreeRecall].
+    pub text_input: TextInput,
+    /// Text edited in [Screen::Edit].
+    pub edit_input: TextInput,
+    /// Set after saving an emptied [EditTarget::CustomQuestion] once, saving again deletes the question.
+    pub confirm_delete: bool,
+    /// If the right answers are shown in [AnswerMode::FreeRecall].
+    pub revealed: bool,
+    /// Which questions are asked.
+    pub selection: Selection,
+    /// Days offered for the [Pool::Mistakes](crate::selection::Pool::Mistakes) in the menu.
+    pub mistake_days: u32,
+    pub screen: Screen,
+    pub menu_state: ListState,
+    /// Short message shown in the title until the next key press.
+    pub status: Option<String>,
+    pub stats: SessionStats,
+    /// State before the last answer, which can be restored until the next answer.
+    pub undo: Option<Undo>,
+    /// Shown in the title, see [CatalogInfo::short_name](crate::metadata::CatalogInfo::short_name).
+    pub catalog_name: Option<String>,
 }
 
 impl App {
@@ -58,32 +95,195 @@ impl App {
     ) -> Self {
         Self {
             exit: false,
+            finished: false,
             item_list_state: ListState::default(),
             question_answer,
             total_progress,
             total_question_count,
             rng: rng(),
+            tick_rate: None,
+            scoring: Scoring::default(),
+            answer_mode: AnswerMode::default(),
+            text_input: TextInput::default(),
+            edit_input: TextInput::default(),
+            confirm_delete: false,
+            revealed: false,
+            selection: Selection::default(),
+            mistake_days: DEFAULT_MISTAKE_DAYS,
+            screen: Screen::default(),
+            menu_state: ListState::default(),
+            status: None,
+            stats: SessionStats::default(),
+            undo: None,
+            catalog_name: None,
+        }
+    }
+
+    /// Returns true if the user answered the current question and is shown the result.
+    pub fn is_answered(&self) -> bool {
+        match self.answer_mode {
+            AnswerMode::Choice => self.question_answer.user_answers.is_some(),
+            AnswerMode::FreeRecall => self.revealed,
+        }
+    }
+
+    /// Returns the congratulation for the user, if every question of the selection is learned.
+    pub fn finished_message(&self) -> Option<&'static str> {
+        match (self.finished, self.selection.filter == Filter::default()) {
+            (false, _) => None,
+            (true, true) => Some("Glückwunsch! Du hast alle Fragen gelernt!"),
+            (true, false) => Some("Glückwunsch! Du hast alle Fragen der Auswahl geschafft!"),
+        }
+    }
+}
+
+/// Everything needed to take back an answer, see [undo](crate::update).
+#[derive(Debug, Clone)]
+pub struct Undo {
+    /// Question as it was before it was answered.
+    pub question_answer: QuestionAnswer,
+    pub text: String,
+    pub revealed: bool,
+    pub stats: SessionStats,
+    /// Id of the row in the table `history` saving the answer.
+    pub history_id: i64,
+    /// `question progress` after the answer.
+    pub new_count: usize,
+}
+
+/// What is shown to the user.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Screen {
+    #[default]
+    Quiz,
+    /// Menu for choosing what and how to train, see [menu](crate::menu).
+    Menu,
+    /// Summary of the session shown before exiting, see [SessionStats].
+    Summary,
+    /// Editor for a text of the current question.
+    Edit(EditTarget),
+}
+
+/// Text of the current question edited by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditTarget {
+    Note,
+    Explanation,
+    /// Correction of the question and its answers, separated by empty lines in order of the catalog.
+    Question,
+    /// Text of the current custom question, see [CustomQuestion::parse_editable_text](crate::custom::CustomQuestion::parse_editable_text).
+    CustomQuestion,
+    /// Text of a new custom question.
+    NewQuestion,
+}
+
+impl EditTarget {
+    /// Name of the text shown to the user.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EditTarget::Note => "Notiz",
+            EditTarget::Explanation => "Erklärung",
+            EditTarget::Question => "Korrektur: Frage und Antworten durch Leerzeilen getrennt",
+            EditTarget::CustomQuestion => {
+                "Eigene Frage: Antworten mit + (richtig) oder - (falsch), leer zum Löschen (mit Rückfrage)"
+            }
+            EditTarget::NewQuestion => {
+                "Neue Frage: Frage, dann Antworten mit + (richtig) oder - (falsch), durch Leerzeilen getrennt"
+            }
+        }
+    }
+}
+
+/// How the user answers questions.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnswerMode {
+    /// The user chooses from the possible answers.
+    #[default]
+    Choice,
+    /// Only the question is shown. The user types the answer, is shown the right answers and grades himself.
+    FreeRecall,
+}
+
+/// Self assessment of the user in [AnswerMode::FreeRecall].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+impl Grade {
+    /// Returns the new `question progress` after grading a question with progress `count`.
+    ///
+    /// - [Grade::Again] resets the progress like a wrong answer.
+    /// - [Grade::Hard] keeps the progress.
+    /// - [Grade::Good] increases the progress by one like a right answer.
+    /// - [Grade::Easy] increases the progress by two.
+    pub fn apply(self, count: usize) -> usize {
+        match self {
+            Grade::Again => 0,
+            Grade::Hard => count,
+            Grade::Good => (count + 1).min(TOTAL_COUNT_TRIES_PER_QUESTION),
+            Grade::Easy => (count + 2).min(TOTAL_COUNT_TRIES_PER_QUESTION),
         }
     }
 }
 
-/// This struct saves a question, the right answer and wrong answers.
+/// How answers to questions with multiple right answers are scored.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scoring {
+    /// Only answers where exactly the right answers were chosen count as correct.
+    #[default]
+    AllOrNothing,
+    /// Answers that are [partially correct](Evaluation::PartiallyCorrect) keep the progress of the question.
+    Partial,
+}
+
+/// Result of comparing the users answers with the right answers of a [QuestionAnswer].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Evaluation {
+    Correct,
+    /// Some right answers were chosen, but less wrong than right ones.
+    PartiallyCorrect,
+    Wrong,
+}
+
+/// This struct saves a question, the right answers and wrong answers.
+///
+/// Any number of possible answers is supported (e.g. two for true/false questions).
+/// More than one answer might be right.
 ///
-/// It also contains functions for [scrambling](QuestionAnswer::scramble) the answers, but with keeping track of the right answer.
+/// It also contains functions for [scrambling](QuestionAnswer::scramble) the answers, but with keeping track of the right answers.
 /// It also holds the users input used for rendering the result to the user.
 ///
 /// ```
-/// let first_question = QuestionAnswer::new(0, "What is 1+1?", vec!["3", "2", "1", "4"], 1);
+/// let first_question = QuestionAnswer::new(0, "What is 1+1?", vec!["3", "2", "1", "4"], vec![1]);
 /// ```
 ///
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct QuestionAnswer {
     pub id: usize,
     pub question: String,
     pub possible_answers: Vec<String>,
-    pub right_answer: usize,
-    pub user_answer: Option<usize>,
+    pub right_answers: Vec<usize>,
+    /// Answers marked by the user before confirming.
+    pub marked: Vec<usize>,
+    pub user_answers: Option<Vec<usize>>,
     pub count_correctly_answered: usize,
+    /// Section of the catalog (e.g. `II. Betriebsverfahren`), if the catalog has sections.
+    pub section: Option<String>,
+    /// Position in the catalog of each entry in `possible_answers`.
+    pub order: Vec<usize>,
+    /// Note of the user, e.g. a mnemonic. Empty if there is none.
+    pub note: String,
+    pub bookmarked: bool,
+    /// Why the right answers are right. Empty if there is none.
+    pub explanation: String,
+    /// Deck of a question written by the user. `None` for questions of the catalog.
+    pub custom_deck: Option<String>,
+    /// Page of the catalog PDF the question starts on. `None` if unknown.
+    pub page: Option<usize>,
 }
 
 impl QuestionAnswer {
@@ -91,33 +291,107 @@ impl QuestionAnswer {
         id: usize,
         question: S,
         possible_answers: Vec<S>,
-        right_answer: usize,
+        right_answers: Vec<usize>,
     ) -> Self {
         Self {
             id,
             question: question.to_string(),
+            order: (0..possible_answers.len()).collect(),
             possible_answers: possible_answers.iter().map(|s| s.to_string()).collect(),
-            right_answer,
-            user_answer: None,
+            right_answers,
+            marked: Vec::new(),
+            user_answers: None,
             count_correctly_answered: 0,
+            section: None,
+            note: String::new(),
+            bookmarked: false,
+            explanation: String::new(),
+            custom_deck: None,
+            page: None,
+        }
+    }
+
+    /// Returns true if more than one answer is right.
+    pub fn has_multiple_right_answers(&self) -> bool {
+        self.right_answers.len() > 1
+    }
+
+    /// Marks answer `i` or unmarks it, if it is already marked.
+    pub fn toggle_mark(&mut self, i: usize) {
+        if let Some(position) = self.marked.iter().position(|&m| m == i) {
+            self.marked.remove(position);
+        } else {
+            self.marked.push(i);
+        }
+    }
+
+    /// Compares `user_answers` with `right_answers`.
+    ///
+    /// Returns [Evaluation::Wrong] if the user did not answer yet.
+    pub fn evaluate(&self) -> Evaluation {
+        let Some(user_answers) = &self.user_answers else {
+            return Evaluation::Wrong;
+        };
+        let hits = user_answers
+            .iter()
+            .filter(|i| self.right_answers.contains(i))
+            .count();
+        let misses = user_answers.len() - hits;
+
+        if misses == 0 && hits == self.right_answers.len() {
+            Evaluation::Correct
+        } else if hits > misses {
+            Evaluation::PartiallyCorrect
+        } else {
+            Evaluation::Wrong
         }
     }
 
     /// Scramble right and wrong answers.
     ///
-    /// `right_answer` always points at the index with the right answer in `possible_answers`.
+    /// `right_answers` always point at the indices with the right answers in `possible_answers`.
     pub fn scramble<R: RngCore>(&mut self, rng: &mut R) {
         let mut index_vec: Vec<usize> = (0..self.possible_answers.len()).collect();
         index_vec.shuffle(rng);
+        self.reorder(&index_vec);
+    }
 
-        self.possible_answers = (0..self.possible_answers.len())
-            .map(|i| self.possible_answers[index_vec[i]].clone())
+    /// Puts `possible_answers[index_vec[i]]` at position `i`.
+    ///
+    /// `right_answers` and `order` are adjusted accordingly.
+    /// `index_vec` has to be a permutation of the indices of `possible_answers`.
+    pub fn reorder(&mut self, index_vec: &[usize]) {
+        debug_assert!(self.is_permutation(index_vec));
+
+        self.possible_answers = index_vec
+            .iter()
+            .map(|&i| self.possible_answers[i].clone())
             .collect();
+        self.order = index_vec.iter().map(|&i| self.order[i]).collect();
 
-        self.right_answer = index_vec
+        self.right_answers = self
+            .right_answers
             .iter()
-            .position(|&i| i == self.right_answer)
-            .unwrap();
+            .map(|right_answer| index_vec.iter().position(|i| i == right_answer).unwrap())
+            .collect();
+        self.right_answers.sort();
+    }
+
+    /// Returns the question and its answers in order of the catalog, separated by empty lines.
+    pub fn to_editable_text(&self) -> String {
+        let mut parts = vec![self.question.clone()];
+        parts.extend((0..self.possible_answers.len()).map(|position| {
+            let i = self.order.iter().position(|&p| p == position).unwrap();
+            self.possible_answers[i].clone()
+        }));
+        parts.join("\n\n")
+    }
+
+    /// Checks if `index_vec` holds every index of `possible_answers` exactly once.
+    pub fn is_permutation(&self, index_vec: &[usize]) -> bool {
+        let mut sorted = index_vec.to_vec();
+        sorted.sort();
+        sorted == (0..self.possible_answers.len()).collect::<Vec<_>>()
     }
 }
 
@@ -128,11 +402,11 @@ mod tests {
 
     #[test]
     fn test_scramble() {
-        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2", "3"], 0);
+        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2", "3"], vec![0]);
         let mut rng = rng();
         for _ in 0..10 {
             q.scramble(&mut rng);
-            assert_eq!("0", q.possible_answers[q.right_answer]);
+            assert_eq!("0", q.possible_answers[q.right_answers[0]]);
         }
         assert!(q.possible_answers.iter().any(|s| s == "1"));
         assert!(q.possible_answers.iter().any(|s| s == "2"));
@@ -141,16 +415,92 @@ mod tests {
 
     #[test]
     fn test_scramble_with_longer_vectors() {
-        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2", "3", "4", "5"], 0);
+        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2", "3", "4", "5"], vec![0]);
         let mut rng = rng();
 
         q.scramble(&mut rng);
 
-        assert_eq!("0", q.possible_answers[q.right_answer]);
+        assert_eq!("0", q.possible_answers[q.right_answers[0]]);
         assert!(q.possible_answers.iter().any(|s| s == "1"));
         assert!(q.possible_answers.iter().any(|s| s == "2"));
         assert!(q.possible_answers.iter().any(|s| s == "3"));
         assert!(q.possible_answers.iter().any(|s| s == "4"));
         assert!(q.possible_answers.iter().any(|s| s == "5"));
     }
+
+    #[test]
+    fn test_scramble_multiple_right_answers() {
+        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2", "3", "4"], vec![0, 3]);
+        let mut rng = rng();
+        for _ in 0..10 {
+            q.scramble(&mut rng);
+            let right: Vec<&str> = q
+                .right_answers
+                .iter()
+                .map(|&i| q.possible_answers[i].as_str())
+                .collect();
+            assert_eq!(right.len(), 2);
+            assert!(right.contains(&"0"));
+            assert!(right.contains(&"3"));
+        }
+    }
+
+    #[test]
+    fn test_to_editable_text_uses_catalog_order() {
+        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2"], vec![0]);
+        q.scramble(&mut rng());
+        assert_eq!(q.to_editable_text(), "nan\n\n0\n\n1\n\n2");
+    }
+
+    #[test]
+    fn test_reorder_restores_scramble() {
+        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2", "3", "4"], vec![0, 3]);
+        let original = q.clone();
+        q.scramble(&mut rng());
+
+        let mut restored = original.clone();
+        restored.reorder(&q.order);
+        assert_eq!(restored, q);
+        assert!(!q.is_permutation(&[0, 1, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2", "3"], vec![0, 1, 2]);
+        assert_eq!(q.evaluate(), Evaluation::Wrong);
+
+        q.user_answers = Some(vec![2, 0, 1]);
+        assert_eq!(q.evaluate(), Evaluation::Correct);
+
+        q.user_answers = Some(vec![0, 1]);
+        assert_eq!(q.evaluate(), Evaluation::PartiallyCorrect);
+
+        q.user_answers = Some(vec![0, 1, 3]);
+        assert_eq!(q.evaluate(), Evaluation::PartiallyCorrect);
+
+        q.user_answers = Some(vec![0, 3]);
+        assert_eq!(q.evaluate(), Evaluation::Wrong);
+
+        q.user_answers = Some(vec![3]);
+        assert_eq!(q.evaluate(), Evaluation::Wrong);
+    }
+
+    #[test]
+    fn test_toggle_mark() {
+        let mut q = QuestionAnswer::new(0, "nan", vec!["0", "1", "2"], vec![0, 1]);
+        q.toggle_mark(1);
+        q.toggle_mark(2);
+        assert_eq!(q.marked, vec![1, 2]);
+        q.toggle_mark(1);
+        assert_eq!(q.marked, vec![2]);
+    }
+
+    #[test]
+    fn test_grade_apply() {
+        assert_eq!(Grade::Again.apply(2), 0);
+        assert_eq!(Grade::Hard.apply(2), 2);
+        assert_eq!(Grade::Good.apply(1), 2);
+        assert_eq!(Grade::Easy.apply(0), 2);
+        assert_eq!(Grade::Easy.apply(2), TOTAL_COUNT_TRIES_PER_QUESTION);
+    }
 }
@@ -14,9 +14,17 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use clap::{arg, Command};
+use clap::{
+    arg, builder::PossibleValue, value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command,
+    ValueEnum,
+};
 use std::env;
 
+use crate::app::{AnswerMode, Scoring};
+use crate::custom::DEFAULT_DECK;
+use crate::pdfparser::Extraction;
+use crate::selection::{is_roman_numeral, Order, DEFAULT_MISTAKE_DAYS, DEFAULT_NO_REPEAT};
+
 use once_cell::sync::Lazy;
 
 static LONG_HELP: Lazy<String> = Lazy::new(|| {
@@ -35,6 +43,29 @@ Anleitung:
 ", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), env!("CARGO_PKG_AUTHORS"), env!("CARGO_PKG_LICENSE"))
 });
 
+/// [DEFAULT_MISTAKE_DAYS] as default of `--fehlerkorb`.
+static DEFAULT_MISTAKE_DAYS_ARG: Lazy<String> = Lazy::new(|| DEFAULT_MISTAKE_DAYS.to_string());
+
+/// [DEFAULT_NO_REPEAT] as default of `--sperre`.
+static DEFAULT_NO_REPEAT_ARG: Lazy<String> = Lazy::new(|| DEFAULT_NO_REPEAT.to_string());
+
+/// Ids of the flags choosing what and how to learn, which are part of a saved session.
+pub const LEARN_FLAGS: [&str; 13] = [
+    "wertung",
+    "modus",
+    "fehlerkorb",
+    "lesezeichen",
+    "sammlung",
+    "reihenfolge",
+    "sperre",
+    "von",
+    "bis",
+    "abschnitt",
+    "nur-neue",
+    "nur-offene",
+    "nur-gemeistert",
+];
+
 /// Returns structure for `clap` to parse cli arguments.
 ///
 /// This crate contains a single function returning `Command` struct from `clap`.
@@ -50,18 +81,227 @@ pub fn commands_and_flags() -> Command {
         .args([
             arg!(--license "Prints license information."),
             arg!(--version "Prints version information."),
+            arg!(--wertung <WERTUNG> "Wertung von Fragen mit mehreren richtigen Antworten.")
+                .value_parser(value_parser!(Scoring))
+                .default_value("alles-oder-nichts"),
+            arg!(--modus <MODUS> "Wie Fragen beantwortet werden.")
+                .value_parser(value_parser!(AnswerMode))
+                .default_value("auswahl"),
+            arg!(--fehlerkorb [TAGE] "Übt nur Fragen, die in den letzten Tagen falsch beantwortet wurden.")
+                .value_parser(value_parser!(u32))
+                .default_missing_value(DEFAULT_MISTAKE_DAYS_ARG.as_str()),
+            arg!(--lesezeichen "Übt nur Fragen mit Lesezeichen.").conflicts_with("fehlerkorb"),
+            arg!(--sammlung [NAME] "Übt nur eigene Fragen, ohne NAME die aller Sammlungen.")
+                .conflicts_with_all(["fehlerkorb", "lesezeichen"]),
+            arg!(--reihenfolge <REIHENFOLGE> "Reihenfolge, in der die Fragen gestellt werden.")
+                .value_parser(value_parser!(Order))
+                .default_value("zufall"),
+            arg!(--sperre <ANZAHL> "Fragen der letzten ANZAHL Antworten werden nicht sofort wiederholt.")
+                .value_parser(value_parser!(u32))
+                .default_value(DEFAULT_NO_REPEAT_ARG.as_str()),
+            arg!(--von <NUMMER> "Übt nur Fragen ab dieser Nummer.")
+                .value_parser(value_parser!(usize)),
+            arg!(--bis <NUMMER> "Übt nur Fragen bis zu dieser Nummer.")
+                .value_parser(value_parser!(usize)),
+            arg!(--abschnitt <ABSCHNITT> "Übt nur Fragen dieses Abschnitts (römische Zahl, z.B. II). Mehrfach angebbar.")
+                .value_parser(parse_section)
+                .action(ArgAction::Append),
+            arg!(--"nur-neue" "Übt nur Fragen, die noch nie beantwortet wurden."),
+            arg!(--"nur-offene" "Übt nur Fragen, die noch nicht gelernt sind (Standard)."),
+            arg!(--"nur-gemeistert" "Wiederholt nur Fragen, die bereits gelernt sind."),
         ])
+        .group(ArgGroup::new("fortschritt").args(["nur-neue", "nur-offene", "nur-gemeistert"]))
         .subcommands([
             Command::new("lade")
                 .about("Lädt eine UBI-Gesamtfragenkatalog-PDF-Datei in die interne Datenbank.")
-                .arg(arg!(<PFAD> "Pfad der PDF-Datei.")),
+                .arg(arg!(<PFAD> "Pfad der PDF- oder Textdatei (.txt), - liest den Text von der Standardeingabe."))
+                .arg(
+                    arg!(--extraktion <VERFAHREN> "Verfahren, mit dem die Fragen aus der PDF-Datei gelesen werden.")
+                        .value_parser(value_parser!(Extraction))
+                        .default_value("text"),
+                )
+                .arg(arg!(--bericht <PFAD> "Pfad des Berichts über nicht erkannte Fragen. Standard: im Ordner der Datenbank.")),
+            Command::new("info")
+                .about("Zeigt an, welcher Katalog wann und woraus geladen wurde."),
+            Command::new("pruefe")
+                .about("Prüft die Datenbank auf Fehler und bietet an, sie zu reparieren."),
+            Command::new("text")
+                .about("Gibt den Text eines Fragenkatalogs aus, wie er vor dem Erkennen der Fragen gelesen wird.")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommand(
+                    Command::new("extrahieren")
+                        .about("Extrahiert den Text einer PDF-Datei, Seiten werden durch einen Seitenvorschub getrennt.")
+                        .arg(arg!(<PFAD> "Pfad der PDF-Datei."))
+                        .arg(arg!(--ausgabe <DATEI> "Schreibt den Text in die Datei statt auf die Standardausgabe.")),
+                ),
+            Command::new("erklaerungen")
+                .about("Lädt Erklärungen zu den Fragen aus einer JSON-Datei. Siehe README.")
+                .arg(arg!(<PFAD> "Pfad der JSON-Datei.")),
+            Command::new("korrekturen")
+                .about("Exportiert oder lädt Korrekturen der Fragen als JSON-Datei. Siehe README.")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommands([
+                    Command::new("exportiere")
+                        .about("Schreibt die Korrekturen des geladenen Katalogs in eine JSON-Datei.")
+                        .arg(arg!(<PFAD> "Pfad der JSON-Datei.")),
+                    Command::new("lade")
+                        .about("Lädt Korrekturen aus einer JSON-Datei.")
+                        .arg(arg!(<PFAD> "Pfad der JSON-Datei.")),
+                ]),
+            Command::new("frage")
+                .about("Verwaltet eigene Fragen, die neben dem Katalog gelernt werden.")
+                .subcommand_required(true)
+                .arg_required_else_help(true)
+                .subcommands([
+                    Command::new("hinzufuegen")
+                        .about("Fügt eine eigene Frage hinzu.")
+                        .arg(arg!(<FRAGE> "Text der Frage."))
+                        .arg(answer_arg("richtig", "Richtige Antwort.").required(true))
+                        .arg(answer_arg("falsch", "Falsche Antwort."))
+                        .arg(deck_arg()),
+                    Command::new("bearbeite")
+                        .about("Ersetzt den Text oder die Antworten einer eigenen Frage.")
+                        .arg(custom_number_arg())
+                        .arg(arg!(--frage <FRAGE> "Neuer Text der Frage."))
+                        .arg(answer_arg("richtig", "Ersetzt die richtigen Antworten."))
+                        .arg(answer_arg("falsch", "Ersetzt die falschen Antworten.")),
+                    Command::new("loesche")
+                        .about("Löscht eine eigene Frage mit ihrem Fortschritt.")
+                        .arg(custom_number_arg()),
+                    Command::new("liste")
+                        .about("Listet die eigenen Fragen auf.")
+                        .arg(arg!(--sammlung <NAME> "Nur Fragen dieser Sammlung.")),
+                    Command::new("exportiere")
+                        .about("Schreibt die Fragen einer Sammlung in eine JSON-Datei. Siehe README.")
+                        .arg(arg!(<PFAD> "Pfad der JSON-Datei."))
+                        .arg(deck_arg()),
+                    Command::new("lade")
+                        .about("Fügt die Fragen einer Sammlung aus einer JSON-Datei hinzu.")
+                        .arg(arg!(<PFAD> "Pfad der JSON-Datei.")),
+                ]),
             Command::new("loesche")
                 .about("Löscht alle Fragen oder den Fortschritt aus der Datenbank.")
                 .subcommand_required(true)
                 .arg_required_else_help(true)
                 .subcommands([
-                    Command::new("fragen").about("Löscht alle Fragen aus der Datenbank"),
+                    Command::new("fragen").about("Löscht alle Fragen des Katalogs aus der Datenbank. Eigene Fragen bleiben erhalten."),
                     Command::new("fortschritt").about("Löscht den Fortschritt aus der Datenbank."),
                 ]),
         ])
 }
+
+/// Whether the command in `matches` only reads the database, so it may run while another instance is learning.
+pub fn is_read_only(matches: &ArgMatches) -> bool {
+    match matches.subcommand() {
+        Some(("info" | "text", _)) => true,
+        Some(("korrekturen", sub_matches)) => {
+            matches!(sub_matches.subcommand(), Some(("exportiere", _)))
+        }
+        Some(("frage", sub_matches)) => {
+            matches!(sub_matches.subcommand(), Some(("liste" | "exportiere", _)))
+        }
+        Some(_) => false,
+        None => matches.get_flag("version") || matches.get_flag("license"),
+    }
+}
+
+impl ValueEnum for Scoring {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Scoring::AllOrNothing, Scoring::Partial]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Scoring::AllOrNothing => PossibleValue::new("alles-oder-nichts")
+                .help("Nur genau die richtigen Antworten zählen als richtig."),
+            Scoring::Partial => PossibleValue::new("teilweise")
+                .help("Teilweise richtige Antworten behalten den Fortschritt der Frage."),
+        })
+    }
+}
+
+impl ValueEnum for Extraction {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Extraction::Text, Extraction::Layout]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Extraction::Text => {
+                PossibleValue::new("text").help("Liest den reinen Text der PDF-Datei.")
+            }
+            Extraction::Layout => PossibleValue::new("layout")
+                .help("Erkennt Fragen, Antworten und Kopfzeilen an ihrer Position auf der Seite."),
+        })
+    }
+}
+
+impl ValueEnum for AnswerMode {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[AnswerMode::Choice, AnswerMode::FreeRecall]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            AnswerMode::Choice => {
+                PossibleValue::new("auswahl").help("Die richtige Antwort wird ausgewählt.")
+            }
+            AnswerMode::FreeRecall => PossibleValue::new("frei")
+                .help("Die Antwort wird frei eingegeben und danach selbst bewertet."),
+        })
+    }
+}
+
+impl ValueEnum for Order {
+    fn value_variants<'a>() -> &'a [Self] {
+        &Order::ALL
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Order::Random => PossibleValue::new("zufall").help("Jede Frage wird zufällig gezogen."),
+            Order::Deck => PossibleValue::new("stapel")
+                .help("Jede offene Frage kommt einmal pro Runde dran, auch über Neustarts hinweg."),
+            Order::Sequential => PossibleValue::new("reihe")
+                .help("Die Fragen kommen nach Nummer, weiter nach der zuletzt beantworteten."),
+            Order::Weakest => PossibleValue::new("schwaechste")
+                .help("Fragen mit der höchsten Fehlerquote kommen zuerst."),
+            Order::Unseen => PossibleValue::new("ungesehen")
+                .help("Nie beantwortete Fragen zuerst, dann die am längsten nicht gesehenen."),
+            Order::Section => PossibleValue::new("abschnitt")
+                .help("Die Fragen kommen Abschnitt für Abschnitt, innerhalb zufällig."),
+        })
+    }
+}
+
+/// Argument `--<name> <ANTWORT>` of `frage`, which may be given multiple times.
+fn answer_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .value_name("ANTWORT")
+        .help(format!("{help} Mehrfach angebbar."))
+        .action(ArgAction::Append)
+}
+
+/// Argument `--sammlung <NAME>` of `frage`.
+fn deck_arg() -> Arg {
+    arg!(--sammlung <NAME> "Sammlung der Fragen.").default_value(DEFAULT_DECK)
+}
+
+/// Argument `<NUMMER>` of a custom question.
+fn custom_number_arg() -> Arg {
+    arg!(<NUMMER> "Nummer der eigenen Frage (siehe `frage liste`).")
+        .value_parser(value_parser!(usize))
+}
+
+/// Parses the roman numeral of a section, lower case is accepted.
+fn parse_section(s: &str) -> Result<String, String> {
+    let numeral = s.trim().trim_end_matches('.').to_uppercase();
+    if is_roman_numeral(&numeral) {
+        Ok(numeral)
+    } else {
+        Err(format!("'{s}' ist keine römische Zahl."))
+    }
+}
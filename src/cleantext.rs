@@ -0,0 +1,156 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use fancy_regex::{Captures, Regex};
+use hyphenation::Hyphenator;
+use once_cell::sync::Lazy;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::dictionary::DICTIONARY;
+
+const SOFT_HYPHEN: char = '\u{AD}';
+
+/// Words after a hyphen at the end of a line, which show that the hyphen stands for a left out word part,
+/// e.g. `Ein- und Ausfahrt`.
+const CONJUNCTIONS: [&str; 5] = ["und", "oder", "bzw", "sowie", "bis"];
+
+/// Cleans text extracted from a PDF.
+///
+/// 1. Normalizes unicode (see [normalize]).
+/// 2. Joins words broken at the end of a line (see [dehyphenate]).
+/// 3. Collapses every run of whitespace into a single space and trims the text.
+///
+/// ```
+/// assert_eq!(clean_text(" UKW-\nSprech-\nfunk\u{A0} zeugnis "), "UKW-Sprechfunk zeugnis");
+/// ```
+pub fn clean_text(s: &str) -> String {
+    dehyphenate(&normalize(s))
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Applies NFKC normalization, which replaces ligatures (e.g. `ﬁ`) and non-breaking spaces.
+///
+/// Soft hyphens at the end of a line join the line with the next, all other soft hyphens are removed.
+/// Unicode hyphens become `-` and line endings become `\n`.
+pub fn normalize(s: &str) -> String {
+    static REG_SOFT_BREAK: Lazy<Regex> =
+        Lazy::new(|| Regex::new("\u{AD}[ \t]*\r?\n[ \t]*").unwrap());
+
+    let s: String = s
+        .nfkc()
+        .map(|c| match c {
+            '\u{2010}' | '\u{2011}' => '-',
+            c => c,
+        })
+        .collect();
+    let s = s.replace("\r\n", "\n");
+    REG_SOFT_BREAK
+        .replace_all(&s, "")
+        .chars()
+        .filter(|&c| c != SOFT_HYPHEN)
+        .collect()
+}
+
+/// Resolves every hyphen at the end of a line.
+///
+/// The hyphen is removed if it only breaks a word, e.g. `Sprech-\nfunk` becomes `Sprechfunk`.
+/// It is kept in compounds with acronyms, numbers or nouns, e.g. `UKW-\nSprechfunk` becomes `UKW-Sprechfunk`,
+/// and before conjunctions, e.g. `Ein-\nund Ausfahrt` becomes `Ein- und Ausfahrt`.
+/// Other words are split at the hyphen only if the hyphenation [DICTIONARY] allows it.
+pub fn dehyphenate(s: &str) -> String {
+    static REG_LINE_END_HYPHEN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?P<left>\w*)-[ \t]*\n[ \t]*(?P<right>\w*)").unwrap());
+
+    REG_LINE_END_HYPHEN
+        .replace_all(s, |caps: &Captures| {
+            let (left, right) = (&caps["left"], &caps["right"]);
+            format!("{left}{}{right}", line_end_hyphen(left, right))
+        })
+        .into_owned()
+}
+
+/// Returns what replaces the hyphen and line break between `left` and `right`.
+fn line_end_hyphen(left: &str, right: &str) -> &'static str {
+    let first_right = right.chars().next();
+    if left.is_empty() || CONJUNCTIONS.contains(&right) {
+        return "- ";
+    }
+    if right.is_empty()
+        || left.ends_with(|c: char| c.is_ascii_digit())
+        || first_right.is_some_and(|c| c.is_uppercase() || c.is_ascii_digit())
+        || is_acronym(left)
+    {
+        return "-";
+    }
+
+    let word = format!("{left}{right}");
+    if DICTIONARY.hyphenate(&word).breaks.contains(&left.len()) {
+        ""
+    } else {
+        "-"
+    }
+}
+
+/// Checks if `word` is an acronym like `UKW` or `DSC`.
+fn is_acronym(word: &str) -> bool {
+    word.chars().count() >= 2 && word.chars().all(|c| c.is_uppercase() || c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Text as extracted from catalogs and how it should look after cleaning.
+    const CORPUS: [(&str, &str); 16] = [
+        ("Sprech-\nfunk", "Sprechfunk"),
+        ("Sprechfunk-\nzeugnis", "Sprechfunkzeugnis"),
+        ("Binnenschiff-\nfahrt", "Binnenschifffahrt"),
+        ("Fahr-\nwasser", "Fahrwasser"),
+        ("UKW-\nSprechfunk", "UKW-Sprechfunk"),
+        ("UKW-\nfunk", "UKW-funk"),
+        ("Schiff-\nSchiff-Verkehr", "Schiff-Schiff-Verkehr"),
+        ("Kanal-\n16", "Kanal-16"),
+        ("Ein-\nund Ausfahrt", "Ein- und Ausfahrt"),
+        ("Haupt- \n  oder Nebenkanal", "Haupt- oder Nebenkanal"),
+        ("Verkehr -\nnicht", "Verkehr - nicht"),
+        (
+            "Die  Frage\nüber   zwei\n\tZeilen ",
+            "Die Frage über zwei Zeilen",
+        ),
+        ("Ober\u{FB02}äche", "Oberfläche"),
+        ("Schall\u{A0}signal", "Schall signal"),
+        ("Not\u{AD}fall", "Notfall"),
+        (
+            "Not\u{AD}\nfall und Not\u{2011}\nSchiff",
+            "Notfall und Not-Schiff",
+        ),
+    ];
+
+    #[test]
+    fn test_corpus() {
+        for (raw, cleaned) in CORPUS {
+            assert_eq!(clean_text(raw), cleaned, "raw: {raw:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_keeps_line_breaks() {
+        assert_eq!(normalize("a\r\nb\u{A0}c"), "a\nb c");
+    }
+}
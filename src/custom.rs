@@ -0,0 +1,234 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+use crate::app::QuestionAnswer;
+
+/// Ids of custom questions start after this offset, so they never collide with the numbers of a catalog.
+pub const CUSTOM_ID_OFFSET: usize = 100_000;
+
+/// Catalog key of custom questions in `notes`, `explanations` and `overrides`, independent of the loaded catalog.
+pub const CUSTOM_CATALOG: &str = "EIGENE";
+
+/// Deck of custom questions created without naming one.
+pub const DEFAULT_DECK: &str = "Eigene Fragen";
+
+/// Returns the id of custom question `number`.
+pub fn custom_id(number: usize) -> usize {
+    CUSTOM_ID_OFFSET + number
+}
+
+/// Returns the number shown to the user of the custom question `id`.
+pub fn custom_number(id: usize) -> usize {
+    id.saturating_sub(CUSTOM_ID_OFFSET)
+}
+
+/// Question written by the user (e.g. an instructor), stored in a deck next to the imported catalog.
+///
+/// In the editor of the TUI it is written as text, see [CustomQuestion::parse_editable_text]:
+/// ```text
+/// Welcher Kanal ist der Anrufkanal?
+///
+/// + Kanal 16
+///
+/// - Kanal 10
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomQuestion {
+    #[serde(rename = "frage")]
+    pub question: String,
+    #[serde(rename = "richtig")]
+    pub right_answers: Vec<String>,
+    #[serde(rename = "falsch", default)]
+    pub wrong_answers: Vec<String>,
+}
+
+impl CustomQuestion {
+    /// Checks that the question has a text, a right answer and at least two answers.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.question.trim().is_empty() {
+            return Err("Die Frage hat keinen Text.".to_owned());
+        }
+        if self.right_answers.is_empty() {
+            return Err("Die Frage braucht mindestens eine richtige Antwort.".to_owned());
+        }
+        if self.right_answers.len() + self.wrong_answers.len() < 2 {
+            return Err("Die Frage braucht mindestens zwei Antworten.".to_owned());
+        }
+        if self
+            .right_answers
+            .iter()
+            .chain(&self.wrong_answers)
+            .any(|answer| answer.trim().is_empty())
+        {
+            return Err("Eine Antwort hat keinen Text.".to_owned());
+        }
+        Ok(())
+    }
+
+    /// Returns the question with `id` in the deck `deck`, right answers come first.
+    pub fn to_question_answer(&self, id: usize, deck: &str) -> QuestionAnswer {
+        let mut q = QuestionAnswer::new(
+            id,
+            &self.question,
+            self.right_answers
+                .iter()
+                .chain(&self.wrong_answers)
+                .collect(),
+            (0..self.right_answers.len()).collect(),
+        );
+        q.custom_deck = Some(deck.to_owned());
+        q
+    }
+
+    /// Returns the custom question of `q` with answers in order of `q.possible_answers`.
+    pub fn from_question_answer(q: &QuestionAnswer) -> Self {
+        let (right, wrong): (Vec<_>, Vec<_>) = q
+            .possible_answers
+            .iter()
+            .enumerate()
+            .partition(|(i, _)| q.right_answers.contains(i));
+        Self {
+            question: q.question.clone(),
+            right_answers: right.into_iter().map(|(_, a)| a.clone()).collect(),
+            wrong_answers: wrong.into_iter().map(|(_, a)| a.clone()).collect(),
+        }
+    }
+
+    /// Parses the question, followed by its answers, each starting with `+` if it is right or `-` if it is wrong.
+    /// Question and answers are separated by empty lines.
+    pub fn parse_editable_text(text: &str) -> Result<Self, String> {
+        let mut parts = text
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|part| !part.is_empty());
+        let mut q = Self {
+            question: parts.next().unwrap_or_default().to_owned(),
+            right_answers: vec![],
+            wrong_answers: vec![],
+        };
+        for part in parts {
+            if let Some(answer) = part.strip_prefix('+') {
+                q.right_answers.push(answer.trim().to_owned());
+            } else if let Some(answer) = part.strip_prefix('-') {
+                q.wrong_answers.push(answer.trim().to_owned());
+            } else {
+                return Err(
+                    "Jede Antwort beginnt mit + (richtig) oder - (falsch) nach einer Leerzeile."
+                        .to_owned(),
+                );
+            }
+        }
+        q.validate()?;
+        Ok(q)
+    }
+
+    /// Returns the text parsed by [CustomQuestion::parse_editable_text].
+    pub fn to_editable_text(&self) -> String {
+        let mut parts = vec![self.question.clone()];
+        parts.extend(self.right_answers.iter().map(|a| format!("+ {a}")));
+        parts.extend(self.wrong_answers.iter().map(|a| format!("- {a}")));
+        parts.join("\n\n")
+    }
+}
+
+/// File with the custom questions of one deck, e.g. to hand them out to students.
+///
+/// ```json
+/// {
+///   "sammlung": "Kurs A",
+///   "fragen": [
+///     { "frage": "Welcher Kanal ist der Anrufkanal?", "richtig": ["Kanal 16"], "falsch": ["Kanal 10"] }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckFile {
+    #[serde(rename = "sammlung")]
+    pub deck: String,
+    #[serde(rename = "fragen")]
+    pub questions: Vec<CustomQuestion>,
+}
+
+/// Reads a [DeckFile] in json format from `path`. Every question is validated.
+pub fn read_deck_file(path: PathBuf) -> Result<DeckFile> {
+    let s = read_to_string(&path)?;
+    parse_deck_file(&s).wrap_err_with(|| format!("Ungültige Fragendatei {}", path.display()))
+}
+
+/// Writes `file` in json format to `path`.
+pub fn write_deck_file(path: PathBuf, file: &DeckFile) -> Result<()> {
+    write(path, serde_json::to_string_pretty(file)? + "\n")?;
+    Ok(())
+}
+
+fn parse_deck_file(s: &str) -> Result<DeckFile> {
+    let file: DeckFile = serde_json::from_str(s)?;
+    for (i, q) in file.questions.iter().enumerate() {
+        q.validate()
+            .map_err(|err| color_eyre::eyre::eyre!("Frage {}: {err}", i + 1))?;
+    }
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_editable_text_roundtrip() {
+        let q = CustomQuestion::parse_editable_text(
+            "Welcher Kanal ist der Anrufkanal?\n\n+ Kanal 16\n\n-Kanal 10\n\n\n- Kanal 22\n",
+        )
+        .unwrap();
+        assert_eq!(q.right_answers, vec!["Kanal 16"]);
+        assert_eq!(q.wrong_answers, vec!["Kanal 10", "Kanal 22"]);
+        assert_eq!(
+            CustomQuestion::parse_editable_text(&q.to_editable_text()),
+            Ok(q.clone())
+        );
+
+        let qa = q.to_question_answer(custom_id(1), DEFAULT_DECK);
+        assert_eq!(qa.right_answers, vec![0]);
+        assert_eq!(CustomQuestion::from_question_answer(&qa), q);
+
+        assert!(CustomQuestion::parse_editable_text("Frage\n\nKanal 16").is_err());
+        assert!(CustomQuestion::parse_editable_text("Frage\n\n- Kanal 16\n\n- Kanal 10").is_err());
+        assert!(CustomQuestion::parse_editable_text("Frage\n\n+ Kanal 16").is_err());
+    }
+
+    #[test]
+    fn test_parse_deck_file() -> Result<()> {
+        let file = parse_deck_file(
+            r#"{ "sammlung": "Kurs A", "fragen": [{ "frage": "1+1?", "richtig": ["2"], "falsch": ["3"] }] }"#,
+        )?;
+        assert_eq!(file.deck, "Kurs A");
+        assert_eq!(file.questions[0].wrong_answers, vec!["3"]);
+
+        assert!(parse_deck_file(
+            r#"{ "sammlung": "Kurs A", "fragen": [{ "frage": "1+1?", "richtig": [] }] }"#
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
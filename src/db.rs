@@ -14,30 +14,133 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use color_eyre::eyre::Result;
-use rusqlite::Connection;
+use std::time::Duration;
+
+use color_eyre::{eyre::Result, Section};
+use rusqlite::{params_from_iter, types::Value, Connection, OpenFlags, OptionalExtension};
 
 use crate::app::QuestionAnswer;
+use crate::custom::{CustomQuestion, CUSTOM_CATALOG, CUSTOM_ID_OFFSET};
 use crate::fs::get_local_dir;
+use crate::integrity::{is_corruption, Problem};
+use crate::metadata::CatalogInfo;
+use crate::overrides::Override;
+use crate::pdfparser::Catalog;
+use crate::selection::{Filter, Order, Selection, SqlCondition};
+use crate::session::Session;
 
 const DB_NAME: &str = "ubilerndb.sqlite3";
-const TOTAL_COUNT_TRIES_PER_QUESTION: usize = 3;
+/// How long a connection waits for a lock on the database, before failing with `SQLITE_BUSY`.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+pub const TOTAL_COUNT_TRIES_PER_QUESTION: usize = 3;
 
 const SQL_CREATE_QUESTION_TABLE: &str = "CREATE TABLE IF NOT EXISTS questions (
       id                            INTEGER PRIMARY KEY,
       question                      TEXT NOT NULL,
-      answers_0                     TEXT NOT NULL,
-      answers_1                     TEXT NOT NULL,
-      answers_2                     TEXT NOT NULL,
-      answers_3                     TEXT NOT NULL,
-      correctly_answered            INTEGER NOT NULL
+      correctly_answered            INTEGER NOT NULL,
+      section                       TEXT,
+      custom_deck                   TEXT,
+      page                          INTEGER
+)";
+
+const SQL_CREATE_ANSWER_TABLE: &str = "CREATE TABLE IF NOT EXISTS answers (
+      question_id                   INTEGER NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+      position                      INTEGER NOT NULL,
+      answer                        TEXT NOT NULL,
+      correct                       INTEGER NOT NULL,
+      PRIMARY KEY (question_id, position)
+)";
+
+const SQL_CREATE_HISTORY_TABLE: &str = "CREATE TABLE IF NOT EXISTS history (
+      id                            INTEGER PRIMARY KEY,
+      question_id                   INTEGER NOT NULL REFERENCES questions(id) ON DELETE CASCADE,
+      answered_at                   TEXT NOT NULL DEFAULT (datetime('now')),
+      correct                       INTEGER NOT NULL
+)";
+
+/// Questions left in the current round of [Order::Deck], drawn in order of `position`.
+const SQL_CREATE_DECK_TABLE: &str = "CREATE TABLE IF NOT EXISTS deck (
+      question_id                   INTEGER PRIMARY KEY REFERENCES questions(id) ON DELETE CASCADE,
+      position                      INTEGER NOT NULL
+)";
+
+/// Notes and bookmarks of the user, keyed by [sql_catalog] and question number.
+const SQL_CREATE_NOTE_TABLE: &str = "CREATE TABLE IF NOT EXISTS notes (
+      catalog                       TEXT NOT NULL,
+      number                        INTEGER NOT NULL,
+      note                          TEXT NOT NULL DEFAULT '',
+      bookmarked                    INTEGER NOT NULL DEFAULT 0,
+      PRIMARY KEY (catalog, number)
+)";
+
+/// Key-value pairs describing the database, e.g. `catalog` is the key of the loaded catalog.
+const SQL_CREATE_META_TABLE: &str = "CREATE TABLE IF NOT EXISTS meta (
+      key                           TEXT PRIMARY KEY,
+      value                         TEXT NOT NULL
+)";
+
+/// Explanations of the questions, keyed by [sql_catalog] and question number.
+const SQL_CREATE_EXPLANATION_TABLE: &str = "CREATE TABLE IF NOT EXISTS explanations (
+      catalog                       TEXT NOT NULL,
+      number                        INTEGER NOT NULL,
+      explanation                   TEXT NOT NULL,
+      PRIMARY KEY (catalog, number)
+)";
+
+/// Corrections of the imported texts, keyed by [sql_catalog] and question number.
+///
+/// `part` 0 is the question, part `n` the `n`-th answer in order of the catalog.
+const SQL_CREATE_OVERRIDE_TABLE: &str = "CREATE TABLE IF NOT EXISTS overrides (
+      catalog                       TEXT NOT NULL,
+      number                        INTEGER NOT NULL,
+      part                          INTEGER NOT NULL,
+      text                          TEXT NOT NULL,
+      PRIMARY KEY (catalog, number, part)
+)";
+
+/// Returns the SQL expression for the catalog key of the question with the id `number` (an SQL expression).
+///
+/// `notes`, `explanations` and `overrides` use this key instead of referencing `questions`,
+/// so they survive reloading the catalog, which replaces the questions.
+/// Custom questions use [CUSTOM_CATALOG], other questions the loaded catalog in the table `meta`,
+/// or an empty key for catalogs loaded by older versions.
+pub(crate) fn sql_catalog(number: &str) -> String {
+    format!(
+        "(CASE WHEN {number} > {CUSTOM_ID_OFFSET} THEN '{CUSTOM_CATALOG}'
+               ELSE coalesce((SELECT value FROM meta WHERE key = 'catalog'), '') END)"
+    )
+}
+
+/// Holds at most one row with the [Session] to resume as json.
+const SQL_CREATE_SESSION_TABLE: &str = "CREATE TABLE IF NOT EXISTS session (
+      id                            INTEGER PRIMARY KEY CHECK (id = 1),
+      state                         TEXT NOT NULL,
+      saved_at                      TEXT NOT NULL DEFAULT (datetime('now'))
 )";
 
+/// Moves the answers of databases created by versions <= 0.1.11 (columns `answers_0` to `answers_3`,
+/// where `answers_0` is the right answer) into the `answers` table.
+const SQL_MIGRATE_FIXED_ANSWER_COLUMNS: &str = "BEGIN;
+      INSERT INTO answers (question_id, position, answer, correct)
+            SELECT id, 0, answers_0, 1 FROM questions
+            UNION ALL SELECT id, 1, answers_1, 0 FROM questions
+            UNION ALL SELECT id, 2, answers_2, 0 FROM questions
+            UNION ALL SELECT id, 3, answers_3, 0 FROM questions;
+      ALTER TABLE questions DROP COLUMN answers_0;
+      ALTER TABLE questions DROP COLUMN answers_1;
+      ALTER TABLE questions DROP COLUMN answers_2;
+      ALTER TABLE questions DROP COLUMN answers_3;
+      COMMIT;";
+
 /// Struct holding the [Connection] with an sqlite database.
 ///
 /// Moreover there are many helper fuctions for manipulating the questions state:
-/// - Inserting questions.
-/// - Getting [QuestionAnswer] structs at random.
+/// - Inserting questions with any number of answers.
+/// - Getting [QuestionAnswer] structs at random or from a shuffled deck out of a [Selection].
+/// - Recording the history of answers.
+/// - Saving the [Session] to resume.
+/// - Notes and bookmarks of questions.
+/// - Explanations of questions.
 /// - Clearing questions.
 /// - Clearing progress.
 ///
@@ -59,15 +162,271 @@ impl DB {
     /// [^note]: via [get_local_dir()]
     ///
     /// If the database or folder does not exist, the database and folder are created.
-    /// The table `questions` is also created holding all necessary information per question.
+    /// The tables `questions` and `answers` are also created holding all necessary information per question.
+    ///
+    /// Errors of a corrupted database suggest `ubilerntui pruefe`.
     pub fn new(db_dir_name: &str) -> Result<Self> {
+        let db_path = get_local_dir(db_dir_name)?.join(DB_NAME);
+        Self::init(Connection::open(&db_path)?).map_err(|err| {
+            if is_corruption(&err) {
+                err.wrap_err(format!(
+                    "Die Datenbank {} ist beschädigt.",
+                    db_path.display()
+                ))
+                .suggestion("Prüfen und reparieren Sie die Datenbank mit `ubilerntui pruefe`.")
+            } else {
+                err
+            }
+        })
+    }
+
+    /// Connects to the database like [DB::new], but neither creates nor migrates tables.
+    ///
+    /// Meant for [DB::check], which also works on databases too broken for [DB::new].
+    pub fn new_unchecked(db_dir_name: &str) -> Result<Self> {
         let db_path = get_local_dir(db_dir_name)?.join(DB_NAME);
         let db = Connection::open(db_path)?;
-        db.execute(SQL_CREATE_QUESTION_TABLE, ())?;
+        db.busy_timeout(BUSY_TIMEOUT)?;
         Ok(Self { db })
     }
 
-    /// Inserts question into database (table `question`).
+    /// Connects to the database like [DB::new], but only for reading.
+    ///
+    /// Meant for another instance, while the running one holds the [InstanceLock](crate::lock::InstanceLock).
+    /// Tables are neither created nor migrated, writing fails.
+    pub fn new_read_only(db_dir_name: &str) -> Result<Self> {
+        let db_path = get_local_dir(db_dir_name)?.join(DB_NAME);
+        let db = Connection::open_with_flags(
+            db_path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY
+                | OpenFlags::SQLITE_OPEN_URI
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        db.busy_timeout(BUSY_TIMEOUT)?;
+        Ok(Self { db })
+    }
+
+    /// Creates missing tables and migrates databases of older versions.
+    ///
+    /// The database is switched to write-ahead logging, so reading instances do not block the writing one.
+    fn init(db: Connection) -> Result<Self> {
+        db.busy_timeout(BUSY_TIMEOUT)?;
+        db.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+        let db = Self { db };
+        db.migrate()?;
+        Ok(db)
+    }
+
+    /// Creates missing tables and columns and moves data of older versions, see [DB::init].
+    fn migrate(&self) -> Result<()> {
+        let db = &self.db;
+        db.pragma_update(None, "foreign_keys", true)?;
+        db.execute(SQL_CREATE_QUESTION_TABLE, ())?;
+        db.execute(SQL_CREATE_ANSWER_TABLE, ())?;
+        db.execute(SQL_CREATE_HISTORY_TABLE, ())?;
+        db.execute(SQL_CREATE_DECK_TABLE, ())?;
+        db.execute(SQL_CREATE_SESSION_TABLE, ())?;
+        db.execute(SQL_CREATE_NOTE_TABLE, ())?;
+        db.execute(SQL_CREATE_META_TABLE, ())?;
+        db.execute(SQL_CREATE_EXPLANATION_TABLE, ())?;
+        db.execute(SQL_CREATE_OVERRIDE_TABLE, ())?;
+
+        if has_column(db, "questions", "answers_0")? {
+            db.execute_batch(SQL_MIGRATE_FIXED_ANSWER_COLUMNS)?;
+        }
+        if !has_column(db, "questions", "section")? {
+            db.execute("ALTER TABLE questions ADD COLUMN section TEXT", ())?;
+        }
+        if !has_column(db, "questions", "custom_deck")? {
+            db.execute("ALTER TABLE questions ADD COLUMN custom_deck TEXT", ())?;
+        }
+        if !has_column(db, "questions", "page")? {
+            db.execute("ALTER TABLE questions ADD COLUMN page INTEGER", ())?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if [DB::migrate] would create or change tables, see [Problem::Outdated].
+    fn is_outdated(&self) -> Result<bool> {
+        let db = &self.db;
+        for table in [
+            "questions",
+            "answers",
+            "history",
+            "deck",
+            "session",
+            "notes",
+            "meta",
+            "explanations",
+            "overrides",
+        ] {
+            if !has_table(db, table)? {
+                return Ok(true);
+            }
+        }
+        Ok(has_column(db, "questions", "answers_0")?
+            || !has_column(db, "questions", "section")?
+            || !has_column(db, "questions", "custom_deck")?
+            || !has_column(db, "questions", "page")?)
+    }
+
+    /// Checks the database for [Problem]s without changing it.
+    ///
+    /// First SQLite's `integrity_check` is run. Only if it finds nothing, rows without their question are searched.
+    /// The invariants the program relies on are checked only for tables of the current version,
+    /// else [Problem::Outdated] is reported instead.
+    /// ```
+    /// let db = DB::new_unchecked("db")?;
+    /// for problem in db.check()? {
+    ///     if problem.is_repairable() {
+    ///         db.repair(&problem)?;
+    ///     }
+    /// }
+    /// ```
+    pub fn check(&self) -> Result<Vec<Problem>> {
+        let messages = self.query_strings("PRAGMA integrity_check")?;
+        if messages != ["ok"] {
+            return Ok(vec![Problem::Integrity(messages)]);
+        }
+
+        let mut problems = vec![];
+        let mut orphans: Vec<(String, i64)> = vec![];
+        {
+            let mut statement = self.db.prepare("PRAGMA foreign_key_check")?;
+            let mut rows = statement.query(())?;
+            while let Some(row) = rows.next()? {
+                orphans.push((row.get(0)?, row.get(1)?));
+            }
+        }
+        orphans.sort();
+        for (table, rowid) in orphans {
+            match problems.last_mut() {
+                Some(Problem::Orphans {
+                    table: last_table,
+                    rowids,
+                }) if *last_table == table => rowids.push(rowid),
+                _ => problems.push(Problem::Orphans {
+                    table,
+                    rowids: vec![rowid],
+                }),
+            }
+        }
+
+        if self.is_outdated()? {
+            problems.push(Problem::Outdated);
+            return Ok(problems);
+        }
+
+        let checks = [
+            (
+                Problem::ProgressOutOfRange as fn(Vec<usize>) -> Problem,
+                format!(
+                    "SELECT id FROM questions
+                      WHERE correctly_answered NOT BETWEEN 0 AND {TOTAL_COUNT_TRIES_PER_QUESTION}"
+                ),
+            ),
+            (
+                Problem::EmptyText,
+                format!(
+                    "SELECT id FROM questions
+                      LEFT JOIN overrides
+                          ON overrides.catalog = {catalog}
+                          AND overrides.number = questions.id
+                          AND overrides.part = 0
+                      WHERE trim(coalesce(overrides.text, question)) = ''
+                  UNION
+                  SELECT question_id FROM answers
+                      LEFT JOIN overrides
+                          ON overrides.catalog = {answer_catalog}
+                          AND overrides.number = answers.question_id
+                          AND overrides.part = answers.position + 1
+                      WHERE trim(coalesce(overrides.text, answer)) = ''",
+                    catalog = sql_catalog("questions.id"),
+                    answer_catalog = sql_catalog("answers.question_id")
+                ),
+            ),
+            (
+                Problem::IncompleteAnswers,
+                "SELECT id FROM questions
+                  WHERE (SELECT count() FROM answers WHERE question_id = id) < 2
+                      OR NOT EXISTS (SELECT 1 FROM answers WHERE question_id = id AND correct)"
+                    .to_owned(),
+            ),
+        ];
+        for (problem, sql) in checks {
+            let ids = self.query_ids(&sql)?;
+            if !ids.is_empty() {
+                problems.push(problem(ids));
+            }
+        }
+
+        Ok(problems)
+    }
+
+    /// Repairs `problem`, if it [is repairable](Problem::is_repairable).
+    pub fn repair(&self, problem: &Problem) -> Result<()> {
+        if !problem.is_repairable() {
+            return Ok(());
+        }
+        match problem {
+            Problem::Integrity(_) => {
+                self.db.execute_batch("REINDEX")?;
+            }
+            Problem::Outdated => self.migrate()?,
+            Problem::Orphans { table, rowids } => {
+                let mut statement = self
+                    .db
+                    .prepare(&format!("DELETE FROM \"{table}\" WHERE rowid = ?1"))?;
+                for rowid in rowids {
+                    statement.execute((rowid,))?;
+                }
+            }
+            Problem::ProgressOutOfRange(_) => {
+                self.db.execute(
+                    "UPDATE questions
+                      SET correctly_answered = max(0, min(correctly_answered, ?1))",
+                    (TOTAL_COUNT_TRIES_PER_QUESTION,),
+                )?;
+            }
+            Problem::EmptyText(_) | Problem::IncompleteAnswers(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Returns the first column of every row of `sql` as text.
+    fn query_strings(&self, sql: &str) -> Result<Vec<String>> {
+        let mut statement = self.db.prepare(sql)?;
+        let strings = statement
+            .query_map((), |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(strings)
+    }
+
+    /// Returns the first column of every row of `sql` as id of a question.
+    fn query_ids(&self, sql: &str) -> Result<Vec<usize>> {
+        let mut statement = self.db.prepare(sql)?;
+        let ids = statement
+            .query_map((), |row| row.get(0))?
+            .collect::<Result<Vec<usize>, _>>()?;
+        Ok(ids)
+    }
+
+    /// Closes the connection to the database.
+    ///
+    /// Unlike dropping [DB], errors while finishing outstanding writes are returned.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.close()?;
+    /// ```
+    pub fn close(self) -> Result<()> {
+        self.db.close().map_err(|(_, err)| err)?;
+        Ok(())
+    }
+
+    /// Inserts question into database (tables `question` and `answers`).
+    ///
+    /// Any number of `false_answers` is supported.
     /// ```
     /// let db = DB::new("db")?;
     /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
@@ -79,57 +438,617 @@ impl DB {
         right_answer: S,
         false_answers: Vec<S>,
     ) -> Result<()> {
-        debug_assert!(false_answers.len() == TOTAL_COUNT_TRIES_PER_QUESTION);
+        let mut possible_answers = vec![right_answer];
+        possible_answers.extend(false_answers);
 
-        self.db.execute(
-                  "INSERT INTO questions (id, question, answers_0, answers_1, answers_2, answers_3, correctly_answered)
-                  VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                  (&id, &question.to_string(), &right_answer.to_string(), &false_answers[0].to_string(), &false_answers[1].to_string(), &false_answers[2].to_string(), 0)
-            )?;
+        self.insert_question(&QuestionAnswer::new(
+            id,
+            question,
+            possible_answers,
+            vec![0],
+        ))
+    }
+
+    /// Inserts [QuestionAnswer] into database (tables `question` and `answers`).
+    ///
+    /// The answers are stored in the order of `possible_answers`, each flagged if it is one of the `right_answers`.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.insert_question(&QuestionAnswer::new(1, "Is 1+1 = 2 ?", vec!["Yes", "No"], vec![0]))?;
+    /// ```
+    pub fn insert_question(&self, q: &QuestionAnswer) -> Result<()> {
+        debug_assert!(q
+            .right_answers
+            .iter()
+            .all(|&i| i < q.possible_answers.len()));
+
+        self.db
+            .prepare_cached(
+                "INSERT INTO questions (id, question, correctly_answered, section, custom_deck, page)
+                  VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?
+            .execute((&q.id, &q.question, 0, &q.section, &q.custom_deck, &q.page))?;
+        self.insert_answers(q)
+    }
+
+    /// Replaces the questions of the catalog with the questions of `catalog` and saves its `info`.
+    ///
+    /// Everything happens in one transaction: If anything fails, the old catalog is kept.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// let catalog = parse_pdf_pages(&read_pdf_pages(path)?)?;
+    /// db.import_catalog(&catalog, &CatalogInfo::new(&catalog, "katalog.pdf", &bytes, Extraction::Text))?;
+    /// ```
+    pub fn import_catalog(&self, catalog: &Catalog, info: &CatalogInfo) -> Result<()> {
+        // Rolls back when dropped without commit.
+        let transaction = self.db.unchecked_transaction()?;
+        self.clear()?;
+        self.set_catalog_info(info)?;
+        for q in &catalog.questions {
+            self.insert_question(q)?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Inserts the answers of `q` into the table `answers`.
+    fn insert_answers(&self, q: &QuestionAnswer) -> Result<()> {
+        let mut statement = self.db.prepare_cached(
+            "INSERT INTO answers (question_id, position, answer, correct)
+                  VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (position, answer) in q.possible_answers.iter().enumerate() {
+            statement.execute((
+                &q.id,
+                &position,
+                answer,
+                q.right_answers.contains(&position),
+            ))?;
+        }
 
         Ok(())
     }
 
-    /// Inserts question as tuple into database (table `question`).
+    /// Returns next question as [QuestionAnswer] out of the questions described by `selection`.
+    ///
+    /// The question is drawn according to `selection.order`. If there are other questions,
+    /// the questions of the last `selection.no_repeat` answers are skipped.
+    /// For [Order::Deck] the deck is shuffled anew, once every question of the selection was answered.
+    ///
+    /// Returns `None` if there is no such question.
     /// ```
     /// let db = DB::new("db")?;
-    /// db.insert( (1, "What is 1+1 ?", "2", vec!["1", "0", "3"]) )?;
+    /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
+    /// let q = db.next_question(&Selection::default())?;
     /// ```
-    pub fn insert_tuple<S: ToString>(
+    pub fn next_question(&self, selection: &Selection) -> Result<Option<QuestionAnswer>> {
+        let order_by = selection.order.sql_order_by();
+        let mut condition = selection.sql_condition();
+        if selection.order == Order::Deck {
+            condition = condition.and(SqlCondition::new("position IS NOT NULL"));
+            if self.query_question(&condition, order_by)?.is_none() {
+                self.shuffle_deck(selection)?;
+            }
+        }
+
+        let no_repeat_condition = condition.clone().and(selection.sql_no_repeat_condition());
+        let q = match self.query_question(&no_repeat_condition, order_by)? {
+            Some(q) => Some(q),
+            None => self.query_question(&condition, order_by)?,
+        };
+
+        let Some(mut q) = q else {
+            return Ok(None);
+        };
+        self.load_answers(&mut q)?;
+        Ok(Some(q))
+    }
+
+    /// Returns the first question fulfilling `condition` sorted by `order_by` without its answers.
+    ///
+    /// The columns of the tables `deck`, `notes` and `explanations` can be used in both.
+    /// A correction of the question text in `overrides` replaces the imported text.
+    fn query_question(
         &self,
-        (id, question, right_answer, false_answers): (usize, S, S, Vec<S>),
-    ) -> Result<()> {
-        self.insert(id, question, right_answer, false_answers)
+        condition: &SqlCondition,
+        order_by: &str,
+    ) -> Result<Option<QuestionAnswer>> {
+        Ok(self
+            .db
+            .query_row(
+                &format!(
+                    "SELECT id, coalesce(overrides.text, question), correctly_answered, section,
+                            coalesce(note, ''), coalesce(bookmarked, 0), coalesce(explanation, ''),
+                            custom_deck, page
+                        FROM questions
+                        LEFT JOIN deck ON deck.question_id = questions.id
+                        LEFT JOIN notes
+                            ON notes.catalog = {catalog}
+                            AND notes.number = questions.id
+                        LEFT JOIN explanations
+                            ON explanations.catalog = {catalog}
+                            AND explanations.number = questions.id
+                        LEFT JOIN overrides
+                            ON overrides.catalog = {catalog}
+                            AND overrides.number = questions.id
+                            AND overrides.part = 0
+                        WHERE {}
+                        ORDER BY {order_by}
+                        LIMIT 1",
+                    condition.sql,
+                    catalog = sql_catalog("questions.id")
+                ),
+                params_from_iter(&condition.params),
+                |f| {
+                    Ok(QuestionAnswer {
+                        id: f.get(0)?,
+                        question: f.get(1)?,
+                        count_correctly_answered: f.get(2)?,
+                        section: f.get(3)?,
+                        note: f.get(4)?,
+                        bookmarked: f.get(5)?,
+                        explanation: f.get(6)?,
+                        custom_deck: f.get(7)?,
+                        page: f.get(8)?,
+                        ..Default::default()
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Puts every question described by `selection` into the deck in random order.
+    fn shuffle_deck(&self, selection: &Selection) -> Result<()> {
+        let condition = selection.sql_condition();
+        self.db.execute(
+            &format!(
+                "INSERT OR REPLACE INTO deck (question_id, position)
+                    SELECT id, RANDOM()
+                    FROM questions
+                    WHERE {}",
+                condition.sql
+            ),
+            params_from_iter(&condition.params),
+        )?;
+        Ok(())
+    }
+
+    /// Returns question `id` as [QuestionAnswer] with answers in the order of the catalog.
+    ///
+    /// Returns `None` if there is no such question.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
+    /// let q = db.get_question(1)?;
+    /// ```
+    pub fn get_question(&self, id: usize) -> Result<Option<QuestionAnswer>> {
+        let condition = SqlCondition::with_params("questions.id = ?", vec![Value::from(id as i64)]);
+        let Some(mut q) = self.query_question(&condition, "questions.id")? else {
+            return Ok(None);
+        };
+        self.load_answers(&mut q)?;
+        Ok(Some(q))
     }
 
-    /// Returns random question as [QuestionAnswer] where question is not answered consecutively correct more than 2 times.
+    /// Returns the count of questions described by `selection`.
     /// ```
     /// let db = DB::new("db")?;
     /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
-    /// let q = db.get_random()?;
+    /// assert_eq!(db.count_questions(&Selection::default())?, 1);
     /// ```
-    pub fn get_random(&self) -> Result<QuestionAnswer> {
+    pub fn count_questions(&self, selection: &Selection) -> Result<usize> {
+        let condition = selection.sql_condition();
         Ok(self.db.query_row(
-            "SELECT id, question, answers_0, answers_1, answers_2, answers_3, correctly_answered
-                        FROM questions
-                        WHERE correctly_answered < 3
-                        ORDER BY RANDOM()
-                        LIMIT 1",
-            (),
-            |f| {
-                let possible_answers = vec![f.get(2)?, f.get(3)?, f.get(4)?, f.get(5)?];
-                Ok(QuestionAnswer {
-                    id: f.get(0)?,
-                    question: f.get(1)?,
-                    possible_answers,
-                    right_answer: 0,
-                    user_answer: None,
-                    count_correctly_answered: f.get(6)?,
-                })
-            },
+            &format!(
+                "SELECT count()
+                  FROM questions
+                  WHERE {}",
+                condition.sql
+            ),
+            params_from_iter(&condition.params),
+            |f| f.get(0),
         )?)
     }
 
+    /// Saves that question `id` was answered `correct` or not in the table `history`.
+    ///
+    /// The question is taken out of the deck for this round.
+    /// Returns the id of the new row.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
+    /// db.record_answer(1, false)?;
+    /// ```
+    pub fn record_answer(&self, id: usize, correct: bool) -> Result<i64> {
+        self.db.execute(
+            "INSERT INTO history (question_id, correct)
+                  VALUES (?1, ?2)",
+            (&id, correct),
+        )?;
+        let row_id = self.db.last_insert_rowid();
+        self.db
+            .execute("DELETE FROM deck WHERE question_id = ?1", (&id,))?;
+        Ok(row_id)
+    }
+
+    /// Saves `note` for question `id`. An empty note deletes the note.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
+    /// db.set_note(1, "1+1 is the first prime.")?;
+    /// ```
+    pub fn set_note(&self, id: usize, note: &str) -> Result<()> {
+        self.db.execute(
+            &format!(
+                "INSERT INTO notes (catalog, number, note)
+                  VALUES ({catalog}, ?1, ?2)
+                  ON CONFLICT (catalog, number) DO UPDATE SET note = excluded.note",
+                catalog = sql_catalog("?1")
+            ),
+            (&id, note),
+        )?;
+        self.delete_empty_note(id)
+    }
+
+    /// Sets or removes the bookmark of question `id`. Without bookmark and note the row is deleted.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
+    /// db.set_bookmark(1, true)?;
+    /// ```
+    pub fn set_bookmark(&self, id: usize, bookmarked: bool) -> Result<()> {
+        self.db.execute(
+            &format!(
+                "INSERT INTO notes (catalog, number, bookmarked)
+                  VALUES ({catalog}, ?1, ?2)
+                  ON CONFLICT (catalog, number) DO UPDATE SET bookmarked = excluded.bookmarked",
+                catalog = sql_catalog("?1")
+            ),
+            (&id, bookmarked),
+        )?;
+        self.delete_empty_note(id)
+    }
+
+    /// Deletes the row of question `id` in `notes`, if it has neither a note nor a bookmark.
+    fn delete_empty_note(&self, id: usize) -> Result<()> {
+        self.db.execute(
+            &format!(
+                "DELETE FROM notes
+                  WHERE catalog = {catalog} AND number = ?1 AND note = '' AND NOT bookmarked",
+                catalog = sql_catalog("?1")
+            ),
+            (&id,),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the value of `key` in the table `meta`.
+    pub fn get_meta(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .db
+            .query_row("SELECT value FROM meta WHERE key = ?1", (key,), |f| {
+                f.get(0)
+            })
+            .optional()?)
+    }
+
+    /// Saves `info` in the table `meta`, fields without value are removed.
+    pub fn set_catalog_info(&self, info: &CatalogInfo) -> Result<()> {
+        for (key, value) in CatalogInfo::KEYS.iter().zip(info.values()) {
+            match value {
+                Some(value) => self.set_meta(key, value)?,
+                None => {
+                    self.db.execute("DELETE FROM meta WHERE key = ?1", (key,))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the [CatalogInfo] of the loaded catalog, if one was loaded.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.set_meta("catalog", "UBI")?;
+    /// assert_eq!(db.catalog_info()?.unwrap().catalog, "UBI");
+    /// ```
+    pub fn catalog_info(&self) -> Result<Option<CatalogInfo>> {
+        let mut values: [Option<String>; CatalogInfo::FIELD_COUNT] = Default::default();
+        for (value, key) in values.iter_mut().zip(CatalogInfo::KEYS) {
+            *value = self.get_meta(key)?;
+        }
+        Ok(CatalogInfo::from_values(values))
+    }
+
+    /// Sets `key` to `value` in the table `meta`.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.set_meta("catalog", "UBI")?;
+    /// assert_eq!(db.get_meta("catalog")?, Some("UBI".to_owned()));
+    /// ```
+    pub fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.db.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            (key, value),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the key of the loaded catalog (e.g. `UBI`).
+    ///
+    /// Returns `None` for catalogs loaded by older versions.
+    pub fn catalog(&self) -> Result<Option<String>> {
+        self.get_meta("catalog")
+    }
+
+    /// Returns the catalog key of question `id` in `notes`, `explanations` and `overrides`, see [sql_catalog].
+    pub fn catalog_key(&self, id: usize) -> Result<String> {
+        Ok(self
+            .db
+            .query_row(&format!("SELECT {}", sql_catalog("?1")), (&id,), |f| {
+                f.get(0)
+            })?)
+    }
+
+    /// Saves `explanation` for question `number` of `catalog`. An empty explanation deletes it.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.set_explanation("UBI", 1, "1+1 is 2 by definition.")?;
+    /// ```
+    pub fn set_explanation(&self, catalog: &str, number: usize, explanation: &str) -> Result<()> {
+        if explanation.is_empty() {
+            self.db.execute(
+                "DELETE FROM explanations WHERE catalog = ?1 AND number = ?2",
+                (catalog, &number),
+            )?;
+        } else {
+            self.db.execute(
+                "INSERT OR REPLACE INTO explanations (catalog, number, explanation)
+                      VALUES (?1, ?2, ?3)",
+                (catalog, &number, explanation),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Saves the correction `text` of `part` of question `number` of `catalog`.
+    ///
+    /// `part` 0 is the question, part `n` the `n`-th answer in order of the catalog.
+    /// An empty text or the text of the loaded catalog deletes the correction.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.set_override("UBI", 1, 0, "What is 1 + 1?")?;
+    /// ```
+    pub fn set_override(
+        &self,
+        catalog: &str,
+        number: usize,
+        part: usize,
+        text: &str,
+    ) -> Result<()> {
+        let imported: Option<String> = if self.catalog()?.as_deref() != Some(catalog) {
+            None
+        } else if part == 0 {
+            self.db
+                .query_row(
+                    "SELECT question FROM questions WHERE id = ?1",
+                    (&number,),
+                    |f| f.get(0),
+                )
+                .optional()?
+        } else {
+            self.db
+                .query_row(
+                    "SELECT answer FROM answers WHERE question_id = ?1 AND position = ?2",
+                    (&number, part - 1),
+                    |f| f.get(0),
+                )
+                .optional()?
+        };
+
+        if text.is_empty() || imported.as_deref() == Some(text) {
+            self.db.execute(
+                "DELETE FROM overrides WHERE catalog = ?1 AND number = ?2 AND part = ?3",
+                (catalog, &number, &part),
+            )?;
+        } else {
+            self.db.execute(
+                "INSERT OR REPLACE INTO overrides (catalog, number, part, text)
+                      VALUES (?1, ?2, ?3, ?4)",
+                (catalog, &number, &part, text),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the corrections of `catalog` ordered by question number and part.
+    pub fn get_overrides(&self, catalog: &str) -> Result<Vec<Override>> {
+        let mut statement = self.db.prepare(
+            "SELECT number, part, text
+                  FROM overrides
+                  WHERE catalog = ?1
+                  ORDER BY number, part",
+        )?;
+        let overrides = statement
+            .query_map((catalog,), |f| {
+                Ok(Override {
+                    number: f.get(0)?,
+                    part: f.get(1)?,
+                    text: f.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(overrides)
+    }
+
+    /// Inserts `q` as new custom question into `deck` and returns its id.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// let q = CustomQuestion::parse_editable_text("1+1?\n\n+ 2\n\n- 3").unwrap();
+    /// let id = db.insert_custom_question(DEFAULT_DECK, &q)?;
+    /// ```
+    ///
+    /// Ids of deleted custom questions are never reused, so notes or explanations written elsewhere
+    /// under that number can't be attached to a different question.
+    pub fn insert_custom_question(&self, deck: &str, q: &CustomQuestion) -> Result<usize> {
+        let transaction = self.db.unchecked_transaction()?;
+        let id: usize = self.db.query_row(
+            "SELECT max(coalesce(max(id), 0),
+                        ?1,
+                        coalesce((SELECT CAST(value AS INTEGER) FROM meta WHERE key = 'last_custom_id'), 0)) + 1
+                  FROM questions",
+            (&CUSTOM_ID_OFFSET,),
+            |f| f.get(0),
+        )?;
+        self.insert_question(&q.to_question_answer(id, deck))?;
+        self.set_meta("last_custom_id", &id.to_string())?;
+        transaction.commit()?;
+        Ok(id)
+    }
+
+    /// Replaces the texts and answers of the custom question `id` by `q`, keeping its progress and notes.
+    ///
+    /// Returns `false` if there is no such custom question.
+    pub fn update_custom_question(&self, id: usize, q: &CustomQuestion) -> Result<bool> {
+        let Some(deck) = self.custom_deck(id)? else {
+            return Ok(false);
+        };
+        self.db.execute(
+            "UPDATE questions SET question = ?2 WHERE id = ?1",
+            (&id, &q.question),
+        )?;
+        self.db
+            .execute("DELETE FROM answers WHERE question_id = ?1", (&id,))?;
+        self.insert_answers(&q.to_question_answer(id, &deck))?;
+        Ok(true)
+    }
+
+    /// Deletes the custom question `id` with its history, notes, explanation and corrections.
+    ///
+    /// Returns `false` if there is no such custom question.
+    pub fn delete_custom_question(&self, id: usize) -> Result<bool> {
+        let count = self.db.execute(
+            "DELETE FROM questions WHERE id = ?1 AND custom_deck IS NOT NULL",
+            (&id,),
+        )?;
+        if count > 0 {
+            for table in ["notes", "explanations", "overrides"] {
+                self.db
+                    .execute(&format!("DELETE FROM {table} WHERE number = ?1"), (&id,))?;
+            }
+        }
+        Ok(count > 0)
+    }
+
+    /// Returns the deck of the custom question `id` or `None` if there is no such custom question.
+    fn custom_deck(&self, id: usize) -> Result<Option<String>> {
+        Ok(self
+            .db
+            .query_row(
+                "SELECT custom_deck FROM questions WHERE id = ?1",
+                (&id,),
+                |f| f.get(0),
+            )
+            .optional()?
+            .flatten())
+    }
+
+    /// Returns the custom questions of `deck` (or of every deck) ordered by id, with answers in stored order.
+    pub fn get_custom_questions(&self, deck: Option<&str>) -> Result<Vec<QuestionAnswer>> {
+        let mut statement = self.db.prepare(
+            "SELECT id
+                  FROM questions
+                  WHERE custom_deck IS NOT NULL AND (?1 IS NULL OR custom_deck = ?1)
+                  ORDER BY id",
+        )?;
+        let ids = statement
+            .query_map((deck,), |f| f.get::<_, usize>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut questions = Vec::with_capacity(ids.len());
+        for id in ids {
+            questions.extend(self.get_question(id)?);
+        }
+        Ok(questions)
+    }
+
+    /// Takes back the answer saved in row `history_id` of the table `history`
+    /// and resets the `question progress` of question `id` to `old_count`.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
+    /// let history_id = db.record_answer(1, false)?;
+    /// db.update_count_correct_answers(1, 0)?;
+    /// db.undo_answer(history_id, 1, 2)?;
+    /// ```
+    pub fn undo_answer(&self, history_id: i64, id: usize, old_count: usize) -> Result<()> {
+        self.db
+            .execute("DELETE FROM history WHERE id = ?1", (&history_id,))?;
+        self.update_count_correct_answers(id, old_count)
+    }
+
+    /// Saves `session` to be resumed on the next start. An older session is replaced.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.save_session(&Session::from_app(&app))?;
+    /// ```
+    pub fn save_session(&self, session: &Session) -> Result<()> {
+        self.db.execute(
+            "INSERT OR REPLACE INTO session (id, state)
+                  VALUES (1, ?1)",
+            (serde_json::to_string(session)?,),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the saved [Session].
+    ///
+    /// Sessions saved by other versions, which can not be read anymore, are dropped.
+    pub fn load_session(&self) -> Result<Option<Session>> {
+        let state: Option<String> = self
+            .db
+            .query_row("SELECT state FROM session WHERE id = 1", (), |f| f.get(0))
+            .optional()?;
+        Ok(state.and_then(|state| serde_json::from_str(&state).ok()))
+    }
+
+    /// Deletes the saved [Session].
+    pub fn clear_session(&self) -> Result<()> {
+        self.db.execute("DELETE FROM session", ())?;
+        Ok(())
+    }
+
+    /// Fills `possible_answers` and `right_answers` of `q` from the `answers` table.
+    ///
+    /// Corrections in `overrides` replace the imported answers.
+    fn load_answers(&self, q: &mut QuestionAnswer) -> Result<()> {
+        let mut statement = self.db.prepare(&format!(
+            "SELECT coalesce(overrides.text, answer), correct
+                  FROM answers
+                  LEFT JOIN overrides
+                      ON overrides.catalog = {answer_catalog}
+                      AND overrides.number = answers.question_id
+                      AND overrides.part = answers.position + 1
+                  WHERE question_id = ?1
+                  ORDER BY position",
+            answer_catalog = sql_catalog("answers.question_id")
+        ))?;
+        let answers = statement
+            .query_map((&q.id,), |f| {
+                Ok((f.get::<_, String>(0)?, f.get::<_, bool>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        q.right_answers = answers
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, correct))| *correct)
+            .map(|(i, _)| i)
+            .collect();
+        q.possible_answers = answers.into_iter().map(|(answer, _)| answer).collect();
+        q.order = (0..q.possible_answers.len()).collect();
+
+        Ok(())
+    }
+
     /// Update `question progress` of question with `id` with new value `new_count`.
     /// ```
     /// let db = DB::new("db")?;
@@ -148,47 +1067,71 @@ impl DB {
         Ok(())
     }
 
-    /// Returns sum of the questions `question progress`.
+    /// Returns sum of the questions `question progress` within the subset of `filter`.
+    ///
+    /// The progress of a question is capped at [TOTAL_COUNT_TRIES_PER_QUESTION].
     /// ```
     /// let db = DB::new("db")?;
     /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
-    /// assert_eq!(db.get_total_progress()?, 0);
+    /// assert_eq!(db.get_total_progress(&Filter::default())?, 0);
     /// db.update_count_correct_answers(1, 2)?;
-    /// assert_eq!(db.get_total_progress()?, 2);
+    /// assert_eq!(db.get_total_progress(&Filter::default())?, 2);
     ///
     /// db.insert(2, "What is 1+2 ?", "3", vec!["1", "0", "2"])?;
-    /// assert_eq!(db.get_total_progress()?, 2);
+    /// assert_eq!(db.get_total_progress(&Filter::default())?, 2);
     /// db.update_count_correct_answers(2, 1)?;
-    /// assert_eq!(db.get_total_progress()?, 3);
+    /// assert_eq!(db.get_total_progress(&Filter::default())?, 3);
     /// ```
-    pub fn get_total_progress(&self) -> Result<usize> {
+    pub fn get_total_progress(&self, filter: &Filter) -> Result<usize> {
+        let condition = filter.sql_subset_condition();
         Ok(self.db.query_row(
-            "SELECT sum(correctly_answered)
-                        FROM questions",
-            (),
+            &format!(
+                "SELECT coalesce(sum(correctly_answered), 0)
+                        FROM questions
+                        WHERE {}",
+                condition.sql
+            ),
+            params_from_iter(&condition.params),
             |f| f.get(0),
         )?)
     }
 
-    /// Returns the amount/count of questions * the max count a question can be answered correct.
+    /// Returns the count of all questions, including custom questions.
+    /// ```
+    /// let db = DB::new("db")?;
+    /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
+    /// assert_eq!(db.count_all_questions()?, 1);
+    /// ```
+    pub fn count_all_questions(&self) -> Result<usize> {
+        Ok(self
+            .db
+            .query_row("SELECT count(*) FROM questions", (), |f| f.get(0))?)
+    }
+
+    /// Returns the amount/count of questions within the subset of `filter` * the max count a question can be answered correct.
     ///
     /// This amounts to the total work of the user
     /// until he answered every question consecutively 3 times correct.
     /// ```
     /// let db = DB::new("db")?;
-    /// assert_eq!(db.get_total_question_count()?, 0);
+    /// assert_eq!(db.get_total_question_count(&Filter::default())?, 0);
     ///
     /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
-    /// assert_eq!(db.get_total_question_count()?, 3);
+    /// assert_eq!(db.get_total_question_count(&Filter::default())?, 3);
     ///
     /// db.insert(2, "What is 1+2 ?", "3", vec!["1", "0", "2"])?;
-    /// assert_eq!(db.get_total_question_count()?, 6);
+    /// assert_eq!(db.get_total_question_count(&Filter::default())?, 6);
     /// ```
-    pub fn get_total_question_count(&self) -> Result<usize> {
+    pub fn get_total_question_count(&self, filter: &Filter) -> Result<usize> {
+        let condition = filter.sql_subset_condition();
         let row_count: usize = self.db.query_row(
-            "SELECT count()
-                  FROM questions",
-            (),
+            &format!(
+                "SELECT count()
+                  FROM questions
+                  WHERE {}",
+                condition.sql
+            ),
+            params_from_iter(&condition.params),
             |f| f.get(0),
         )?;
 
@@ -213,7 +1156,9 @@ impl DB {
         Ok(row_count == 0)
     }
 
-    /// Clear all rows in `questions` table.
+    /// Clear all rows of the catalog in `questions` table and the saved [Session].
+    ///
+    /// Custom questions are kept.
     /// ```
     /// let db = DB::new("db")?;
     /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
@@ -223,19 +1168,21 @@ impl DB {
     /// assert!(db.is_empty()?);
     /// ```
     pub fn clear(&self) -> Result<()> {
-        self.db.execute("DELETE FROM questions", ())?;
+        self.db
+            .execute("DELETE FROM questions WHERE custom_deck IS NULL", ())?;
+        self.db.execute("DELETE FROM session", ())?;
         Ok(())
     }
 
-    /// Resets `question progress` of every question.
+    /// Resets `question progress` of every question and deletes the history of answers and the deck.
     /// ```
     /// let db = DB::new("db")?;
     /// db.insert(1, "What is 1+1 ?", "2", vec!["1", "0", "3"])?;
     /// db.update_count_correct_answers(1, 2)?;
-    /// assert_eq!(db.get_total_progress()?, 2);
+    /// assert_eq!(db.get_total_progress(&Filter::default())?, 2);
     ///
     /// db.clear_progress()?;
-    /// assert_eq!(db.get_total_progress()?, 0);
+    /// assert_eq!(db.get_total_progress(&Filter::default())?, 0);
     /// ```
     pub fn clear_progress(&self) -> Result<()> {
         self.db.execute(
@@ -243,6 +1190,8 @@ impl DB {
                   SET correctly_answered = 0",
             (),
         )?;
+        self.db.execute("DELETE FROM history", ())?;
+        self.db.execute("DELETE FROM deck", ())?;
 
         Ok(())
     }
@@ -269,16 +1218,41 @@ impl DB {
     }
 }
 
+/// Checks if `table` has a column named `column`.
+fn has_column(db: &Connection, table: &str, column: &str) -> Result<bool> {
+    let count: usize = db.query_row(
+        "SELECT count()
+              FROM pragma_table_info(?1)
+              WHERE name = ?2",
+        (table, column),
+        |f| f.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+/// Checks if a table named `table` exists.
+fn has_table(db: &Connection, table: &str) -> Result<bool> {
+    let count: usize = db.query_row(
+        "SELECT count()
+              FROM sqlite_master
+              WHERE type = 'table' AND name = ?1",
+        (table,),
+        |f| f.get(0),
+    )?;
+    Ok(count > 0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::custom::DEFAULT_DECK;
+    use crate::pdfparser::{CatalogFormat, Extraction};
+    use crate::selection::{Pool, Progress};
 
     impl DB {
         /// Returns database, which works in memory. This is for testing purposes.
-        fn new_in_memory() -> Result<Self> {
-            let db = Connection::open_in_memory()?;
-            db.execute(SQL_CREATE_QUESTION_TABLE, ())?;
-            Ok(Self { db })
+        pub(crate) fn new_in_memory() -> Result<Self> {
+            Self::init(Connection::open_in_memory()?)
         }
     }
 
@@ -290,10 +1264,10 @@ mod tests {
 
         db.insert(1, "nan", right_answer, false_answers.clone())?;
 
-        let q = db.get_random()?;
+        let q = db.next_question(&Selection::default())?.unwrap();
 
         assert_eq!(q.id, 1);
-        assert_eq!(q.right_answer, 0);
+        assert_eq!(q.right_answers, vec![0]);
         assert_eq!(q.possible_answers[0], "0");
         assert_eq!(q.possible_answers[1..4], false_answers);
         assert_eq!(q.question, "nan");
@@ -301,6 +1275,79 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_insertion_with_variable_answer_count() -> Result<()> {
+        let db = DB::new_in_memory()?;
+
+        db.insert_question(&QuestionAnswer::new(
+            1,
+            "true?",
+            vec!["false", "true"],
+            vec![1],
+        ))?;
+        let q = db.next_question(&Selection::default())?.unwrap();
+        assert_eq!(q.possible_answers, vec!["false", "true"]);
+        assert_eq!(q.right_answers, vec![1]);
+
+        db.clear()?;
+        db.insert(2, "nan", "0", vec!["1", "2", "3", "4"])?;
+        let q = db.next_question(&Selection::default())?.unwrap();
+        assert_eq!(q.possible_answers, vec!["0", "1", "2", "3", "4"]);
+        assert_eq!(q.right_answers, vec![0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_insertion_with_multiple_right_answers() -> Result<()> {
+        let db = DB::new_in_memory()?;
+
+        db.insert_question(&QuestionAnswer::new(
+            1,
+            "nan",
+            vec!["0", "1", "2", "3"],
+            vec![1, 3],
+        ))?;
+        let q = db.next_question(&Selection::default())?.unwrap();
+        assert_eq!(q.right_answers, vec![1, 3]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_fixed_answer_columns() -> Result<()> {
+        let db = Connection::open_in_memory()?;
+        db.execute_batch(
+            "CREATE TABLE questions (
+                  id INTEGER PRIMARY KEY,
+                  question TEXT NOT NULL,
+                  answers_0 TEXT NOT NULL,
+                  answers_1 TEXT NOT NULL,
+                  answers_2 TEXT NOT NULL,
+                  answers_3 TEXT NOT NULL,
+                  correctly_answered INTEGER NOT NULL
+            );
+            INSERT INTO questions VALUES (7, 'nan', '0', '1', '2', '3', 2);",
+        )?;
+
+        let db = DB::init(db)?;
+        assert!(!has_column(&db.db, "questions", "answers_0")?);
+
+        let q = db.next_question(&Selection::default())?.unwrap();
+        assert_eq!(q.id, 7);
+        assert_eq!(q.count_correctly_answered, 2);
+        assert_eq!(q.possible_answers, vec!["0", "1", "2", "3"]);
+        assert_eq!(q.right_answers, vec![0]);
+
+        db.clear()?;
+        let answer_count: usize = db
+            .db
+            .query_row("SELECT count() FROM answers", (), |f| f.get(0))?;
+        assert_eq!(answer_count, 0);
+
+        Ok(())
+    }
+
     #[test]
     fn test_update_count_correct_answers() -> Result<()> {
         let db = DB::new_in_memory()?;
@@ -308,12 +1355,12 @@ mod tests {
         let false_answers = vec!["1", "2", "3"];
         db.insert(1, "nan", right_answer, false_answers.clone())?;
 
-        let q = db.get_random()?;
+        let q = db.next_question(&Selection::default())?.unwrap();
         assert_eq!(q.count_correctly_answered, 0);
 
         db.update_count_correct_answers(1, 2)?;
 
-        let q = db.get_random()?;
+        let q = db.next_question(&Selection::default())?.unwrap();
         assert_eq!(q.count_correctly_answered, 2);
 
         Ok(())
@@ -328,12 +1375,19 @@ mod tests {
 
         db.update_count_correct_answers(1, 2)?;
 
-        assert_eq!(db.get_total_progress()?, 2);
+        assert_eq!(db.get_total_progress(&Filter::default())?, 2);
 
         db.insert(2, "nan", right_answer, false_answers.clone())?;
         db.update_count_correct_answers(2, 3)?;
 
-        assert_eq!(db.get_total_progress()?, 5);
+        assert_eq!(db.get_total_progress(&Filter::default())?, 5);
+
+        let subset = Filter {
+            from: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(db.get_total_progress(&subset)?, 3);
+        assert_eq!(db.get_total_question_count(&subset)?, 3);
 
         Ok(())
     }
@@ -347,7 +1401,7 @@ mod tests {
         db.insert(2, "nan", right_answer, false_answers.clone())?;
 
         assert_eq!(
-            db.get_total_question_count()?,
+            db.get_total_question_count(&Filter::default())?,
             TOTAL_COUNT_TRIES_PER_QUESTION * 2
         );
 
@@ -398,4 +1452,563 @@ mod tests {
         assert!(!db.no_open_questions()?);
         Ok(())
     }
+
+    #[test]
+    fn test_mistakes_pool() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let mistakes = Selection {
+            pool: Pool::Mistakes { days: 7 },
+            ..Default::default()
+        };
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        db.insert(2, "nan", "0", vec!["1", "2", "3"])?;
+        db.insert(3, "nan", "0", vec!["1", "2", "3"])?;
+        assert_eq!(db.next_question(&mistakes)?, None);
+
+        db.record_answer(1, false)?;
+        db.record_answer(2, true)?;
+        db.record_answer(3, false)?;
+        db.record_answer(3, true)?;
+        assert_eq!(db.count_questions(&mistakes)?, 1);
+        assert_eq!(db.next_question(&mistakes)?.unwrap().id, 1);
+
+        db.record_answer(1, true)?;
+        db.record_answer(3, false)?;
+        assert_eq!(db.next_question(&mistakes)?.unwrap().id, 3);
+
+        db.db.execute(
+            "UPDATE history SET answered_at = datetime('now', '-8 days')",
+            (),
+        )?;
+        assert_eq!(db.count_questions(&mistakes)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deck_order() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let deck = Selection {
+            order: Order::Deck,
+            ..Default::default()
+        };
+        for id in 1..=5 {
+            db.insert(id, "nan", "0", vec!["1", "2", "3"])?;
+        }
+
+        for _ in 0..3 {
+            let mut round: Vec<usize> = (0..5)
+                .map(|_| {
+                    let q = db.next_question(&deck)?.unwrap();
+                    db.record_answer(q.id, false)?;
+                    Ok(q.id)
+                })
+                .collect::<Result<_>>()?;
+            round.sort();
+            assert_eq!(round, vec![1, 2, 3, 4, 5]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_deck_survives_reconnect() -> Result<()> {
+        // Deleted with its content when dropped, also if an assertion fails.
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(DB_NAME);
+        let deck = Selection {
+            order: Order::Deck,
+            ..Default::default()
+        };
+        let mut asked = vec![];
+        {
+            let db = DB::init(Connection::open(&path)?)?;
+            for id in 1..=4 {
+                db.insert(id, "nan", "0", vec!["1", "2", "3"])?;
+            }
+            for _ in 0..2 {
+                let q = db.next_question(&deck)?.unwrap();
+                db.record_answer(q.id, true)?;
+                asked.push(q.id);
+            }
+            db.close()?;
+        }
+        let db = DB::init(Connection::open(&path)?)?;
+        for _ in 0..2 {
+            let q = db.next_question(&deck)?.unwrap();
+            db.record_answer(q.id, true)?;
+            asked.push(q.id);
+        }
+        db.close()?;
+
+        asked.sort();
+        assert_eq!(asked, vec![1, 2, 3, 4]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_repeat() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let selection = Selection {
+            no_repeat: 2,
+            ..Default::default()
+        };
+        for id in 1..=3 {
+            db.insert(id, "nan", "0", vec!["1", "2", "3"])?;
+        }
+        db.record_answer(1, false)?;
+        db.record_answer(2, false)?;
+        for _ in 0..20 {
+            assert_eq!(db.next_question(&selection)?.unwrap().id, 3);
+        }
+
+        // Falls back to recent questions if nothing else is left.
+        db.update_count_correct_answers(3, TOTAL_COUNT_TRIES_PER_QUESTION)?;
+        assert!(db.next_question(&selection)?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_orders() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let with_order = |order| Selection {
+            order,
+            no_repeat: 0,
+            ..Default::default()
+        };
+        for (id, section) in [(1, "II. b"), (2, "I. a"), (3, "I. a")] {
+            let mut q = QuestionAnswer::new(id, "nan", vec!["0", "1"], vec![0]);
+            q.section = Some(section.to_owned());
+            db.insert_question(&q)?;
+        }
+        // Section `I. a` starts with question 2 in the catalog, thus after `II. b`.
+        assert_eq!(
+            db.next_question(&with_order(Order::Section))?.unwrap().id,
+            1
+        );
+
+        let sequential = with_order(Order::Sequential);
+        assert_eq!(db.next_question(&sequential)?.unwrap().id, 1);
+        db.record_answer(1, true)?;
+        assert_eq!(db.next_question(&sequential)?.unwrap().id, 2);
+        db.record_answer(2, false)?;
+        assert_eq!(db.next_question(&sequential)?.unwrap().id, 3);
+        db.record_answer(3, true)?;
+        assert_eq!(db.next_question(&sequential)?.unwrap().id, 1);
+
+        assert_eq!(
+            db.next_question(&with_order(Order::Weakest))?.unwrap().id,
+            2
+        );
+
+        db.record_answer(1, true)?;
+        db.record_answer(2, true)?;
+        assert_eq!(db.next_question(&with_order(Order::Unseen))?.unwrap().id, 3);
+        db.insert(4, "nan", "0", vec!["1"])?;
+        assert_eq!(db.next_question(&with_order(Order::Unseen))?.unwrap().id, 4);
+        db.record_answer(2, true)?;
+        assert_eq!(
+            db.next_question(&with_order(Order::Weakest))?.unwrap().id,
+            4
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        for (id, section) in [(1, "I. a"), (2, "I. a"), (3, "II. b"), (4, "III. c")] {
+            let mut q = QuestionAnswer::new(id, "nan", vec!["0", "1"], vec![0]);
+            q.section = Some(section.to_owned());
+            db.insert_question(&q)?;
+        }
+        let with_filter = |filter| Selection {
+            filter,
+            ..Default::default()
+        };
+
+        let range = Filter {
+            from: Some(2),
+            to: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(db.count_questions(&with_filter(range))?, 2);
+
+        let sections = Filter {
+            sections: vec!["I".to_owned(), "III".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(db.count_questions(&with_filter(sections))?, 3);
+        let injected = Filter {
+            sections: vec!["I' OR '1' = '1".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(db.count_questions(&with_filter(injected))?, 0);
+
+        db.update_count_correct_answers(1, TOTAL_COUNT_TRIES_PER_QUESTION)?;
+        db.record_answer(2, false)?;
+        let progress = |progress| Filter {
+            progress,
+            ..Default::default()
+        };
+        assert_eq!(
+            db.count_questions(&with_filter(progress(Progress::Open)))?,
+            3
+        );
+        assert_eq!(
+            db.count_questions(&with_filter(progress(Progress::New)))?,
+            2
+        );
+        let mastered = with_filter(progress(Progress::Mastered));
+        assert_eq!(db.next_question(&mastered)?.unwrap().id, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_roundtrip() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        assert_eq!(db.load_session()?, None);
+
+        let mut q = db.next_question(&Selection::default())?.unwrap();
+        q.scramble(&mut rand::rng());
+        q.toggle_mark(2);
+        let mut app = crate::app::App::new(q, 0, 3);
+        app.selection.pool = Pool::Mistakes { days: 3 };
+        app.item_list_state.select(Some(1));
+        let session = Session::from_app(&app);
+
+        db.save_session(&session)?;
+        assert_eq!(db.load_session()?, Some(session.clone()));
+        let restored = db.load_session()?.unwrap().into_app(&db)?.unwrap();
+        assert_eq!(restored.question_answer, app.question_answer);
+        assert_eq!(restored.selection, app.selection);
+        assert_eq!(restored.item_list_state.selected(), Some(1));
+
+        db.clear()?;
+        assert_eq!(db.load_session()?, None);
+        db.insert(1, "nan", "0", vec!["1"])?;
+        assert!(session.into_app(&db)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_undo_answer() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let mistakes = Selection {
+            pool: Pool::Mistakes { days: 7 },
+            ..Default::default()
+        };
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        db.update_count_correct_answers(1, 2)?;
+
+        let history_id = db.record_answer(1, false)?;
+        db.update_count_correct_answers(1, 0)?;
+        assert_eq!(db.count_questions(&mistakes)?, 1);
+
+        db.undo_answer(history_id, 1, 2)?;
+        assert_eq!(db.count_questions(&mistakes)?, 0);
+        assert_eq!(db.get_total_progress(&Filter::default())?, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_notes_and_bookmarks() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let bookmarks = Selection {
+            pool: Pool::Bookmarks,
+            ..Default::default()
+        };
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        db.insert(2, "nan", "0", vec!["1", "2", "3"])?;
+        assert_eq!(db.count_questions(&bookmarks)?, 0);
+
+        db.set_note(1, "line 1\nline 2")?;
+        db.set_bookmark(2, true)?;
+        db.set_bookmark(1, true)?;
+        db.set_bookmark(1, false)?;
+
+        let q = db.get_question(1)?.unwrap();
+        assert_eq!(q.note, "line 1\nline 2");
+        assert!(!q.bookmarked);
+        let q = db.next_question(&bookmarks)?.unwrap();
+        assert_eq!(q.id, 2);
+        assert!(q.bookmarked);
+        assert_eq!(q.note, "");
+
+        // Notes survive reloading the catalog, but belong to it.
+        db.clear()?;
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        assert_eq!(db.get_question(1)?.unwrap().note, "line 1\nline 2");
+        db.set_meta("catalog", "SRC")?;
+        assert_eq!(db.get_question(1)?.unwrap().note, "");
+
+        // Empty notes without bookmark are deleted.
+        db.set_note(1, "kurz")?;
+        db.set_bookmark(1, true)?;
+        db.set_note(1, "")?;
+        assert!(db.get_question(1)?.unwrap().bookmarked);
+        db.set_bookmark(1, false)?;
+        let note_count: usize =
+            db.db
+                .query_row("SELECT count() FROM notes WHERE catalog = 'SRC'", (), |f| {
+                    f.get(0)
+                })?;
+        assert_eq!(note_count, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_explanations() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        db.set_explanation("UBI", 1, "because")?;
+        db.set_explanation("SRC", 1, "weil")?;
+        assert_eq!(db.get_question(1)?.unwrap().explanation, "");
+
+        db.set_meta("catalog", "SRC")?;
+        assert_eq!(db.get_question(1)?.unwrap().explanation, "weil");
+
+        // Explanations survive reloading the catalog.
+        db.clear()?;
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        assert_eq!(db.get_question(1)?.unwrap().explanation, "weil");
+
+        db.set_explanation("SRC", 1, "")?;
+        assert_eq!(db.get_question(1)?.unwrap().explanation, "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_questions() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        let q = CustomQuestion::parse_editable_text("1+1?\n\n+ 2\n\n- 3").unwrap();
+        let id = db.insert_custom_question("Kurs 'A'", &q)?;
+        assert_eq!(id, CUSTOM_ID_OFFSET + 1);
+        assert_eq!(db.insert_custom_question(DEFAULT_DECK, &q)?, id + 1);
+
+        let selection = Selection {
+            pool: Pool::Custom {
+                deck: Some("Kurs 'A'".to_owned()),
+            },
+            ..Default::default()
+        };
+        assert_eq!(db.count_questions(&selection)?, 1);
+        let custom = db.next_question(&selection)?.unwrap();
+        assert_eq!(custom.custom_deck.as_deref(), Some("Kurs 'A'"));
+        assert_eq!(custom.possible_answers, vec!["2", "3"]);
+
+        // Notes and explanations of custom questions don't depend on the loaded catalog.
+        assert_eq!(db.catalog_key(id)?, CUSTOM_CATALOG);
+        assert_eq!(db.catalog_key(1)?, "");
+        db.set_note(id, "Notiz")?;
+        db.set_explanation(&db.catalog_key(id)?, id, "Addition")?;
+        db.set_meta("catalog", "SRC")?;
+        assert_eq!(db.catalog_key(1)?, "SRC");
+        let custom = db.get_question(id)?.unwrap();
+        assert_eq!(custom.note, "Notiz");
+        assert_eq!(custom.explanation, "Addition");
+
+        // Custom questions survive reloading the catalog.
+        db.clear()?;
+        assert_eq!(db.get_custom_questions(None)?.len(), 2);
+
+        let edited = CustomQuestion::parse_editable_text("1+2?\n\n- 2\n\n+ 3").unwrap();
+        assert!(db.update_custom_question(id, &edited)?);
+        let custom = db.get_question(id)?.unwrap();
+        assert_eq!(custom.question, "1+2?");
+        assert_eq!(CustomQuestion::from_question_answer(&custom), edited);
+
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        assert!(!db.update_custom_question(1, &edited)?);
+        assert!(!db.delete_custom_question(1)?);
+        db.set_override(CUSTOM_CATALOG, id, 1, "zwei")?;
+        assert!(db.delete_custom_question(id)?);
+        assert_eq!(db.get_custom_questions(Some("Kurs 'A'"))?, vec![]);
+        for table in ["explanations", "overrides"] {
+            let count: usize =
+                db.db
+                    .query_row(&format!("SELECT count(*) FROM {table}"), (), |f| f.get(0))?;
+            assert_eq!(count, 0);
+        }
+
+        // Ids of deleted custom questions are not reused.
+        assert!(db.delete_custom_question(id + 1)?);
+        assert_eq!(db.insert_custom_question(DEFAULT_DECK, &q)?, id + 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_page() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let mut q = QuestionAnswer::new(1, "nan", vec!["0", "1"], vec![0]);
+        q.page = Some(42);
+        db.insert_question(&q)?;
+        db.insert(2, "nan", "0", vec!["1"])?;
+
+        assert_eq!(db.get_question(1)?.unwrap().page, Some(42));
+        assert_eq!(db.get_question(2)?.unwrap().page, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_overrides() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        db.set_meta("catalog", "UBI")?;
+        db.set_override("UBI", 1, 0, "What is 1 - 1?")?;
+        db.set_override("UBI", 1, 3, "two")?;
+        db.set_override("SRC", 1, 1, "null")?;
+
+        let q = db.get_question(1)?.unwrap();
+        assert_eq!(q.question, "What is 1 - 1?");
+        assert_eq!(q.possible_answers, vec!["0", "1", "two", "3"]);
+        assert_eq!(q.right_answers, vec![0]);
+
+        // Overrides survive reloading the catalog.
+        db.clear()?;
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        assert_eq!(db.get_question(1)?.unwrap().question, "What is 1 - 1?");
+
+        // Setting the imported text deletes the override.
+        db.set_override("UBI", 1, 0, "nan")?;
+        assert_eq!(
+            db.get_overrides("UBI")?,
+            vec![Override {
+                number: 1,
+                part: 3,
+                text: "two".to_owned()
+            }]
+        );
+        assert_eq!(db.get_overrides("SRC")?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_catalog() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let question = |id| QuestionAnswer::new(id, "nan", vec!["0", "1"], vec![0]);
+        let mut catalog = Catalog {
+            format: CatalogFormat::Ubi,
+            questions: vec![question(1), question(2)],
+            issues: vec![],
+            title: None,
+            edition: None,
+        };
+        let info = CatalogInfo::new(&catalog, "ubi.pdf", b"", Extraction::Text);
+        db.import_catalog(&catalog, &info)?;
+        assert!(db.get_question(2)?.is_some());
+        assert_eq!(db.catalog_info()?, Some(info));
+
+        // A failing insert keeps the old catalog.
+        catalog.format = CatalogFormat::Src;
+        catalog.questions = vec![question(3), question(3)];
+        let src_info = CatalogInfo {
+            catalog: "SRC".to_owned(),
+            ..Default::default()
+        };
+        assert!(db.import_catalog(&catalog, &src_info).is_err());
+        assert!(db.get_question(1)?.is_some());
+        assert!(db.get_question(2)?.is_some());
+        assert!(db.get_question(3)?.is_none());
+        assert_eq!(db.catalog()?.as_deref(), Some("UBI"));
+
+        catalog.questions = vec![question(3)];
+        db.import_catalog(&catalog, &src_info)?;
+        assert!(db.get_question(1)?.is_none());
+        assert_eq!(db.catalog_info()?, Some(src_info));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_and_repair() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        for id in 1..=3 {
+            db.insert_question(&QuestionAnswer::new(id, "nan", vec!["0", "1"], vec![0]))?;
+        }
+        assert_eq!(db.check()?, vec![]);
+
+        db.db.execute_batch(
+            "UPDATE questions SET correctly_answered = 9 WHERE id = 1;
+             UPDATE answers SET answer = ' ' WHERE question_id = 2 AND position = 1;
+             DELETE FROM answers WHERE question_id = 3 AND position = 0;
+             PRAGMA foreign_keys = OFF;
+             INSERT INTO history (id, question_id, correct) VALUES (5, 7, 1);
+             PRAGMA foreign_keys = ON;",
+        )?;
+        let problems = db.check()?;
+        assert_eq!(
+            problems,
+            vec![
+                Problem::Orphans {
+                    table: "history".to_owned(),
+                    rowids: vec![5]
+                },
+                Problem::ProgressOutOfRange(vec![1]),
+                Problem::EmptyText(vec![2]),
+                Problem::IncompleteAnswers(vec![3]),
+            ]
+        );
+
+        for problem in problems.iter().filter(|problem| problem.is_repairable()) {
+            db.repair(problem)?;
+        }
+        assert_eq!(
+            db.check()?,
+            vec![
+                Problem::EmptyText(vec![2]),
+                Problem::IncompleteAnswers(vec![3])
+            ]
+        );
+        let progress: usize = db.db.query_row(
+            "SELECT correctly_answered FROM questions WHERE id = 1",
+            (),
+            |row| row.get(0),
+        )?;
+        assert_eq!(progress, TOTAL_COUNT_TRIES_PER_QUESTION);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_outdated() -> Result<()> {
+        let db = DB {
+            db: Connection::open_in_memory()?,
+        };
+        db.db.execute_batch(
+            "CREATE TABLE questions (
+                  id INTEGER PRIMARY KEY,
+                  question TEXT NOT NULL,
+                  answers_0 TEXT NOT NULL,
+                  answers_1 TEXT NOT NULL,
+                  answers_2 TEXT NOT NULL,
+                  answers_3 TEXT NOT NULL,
+                  correctly_answered INTEGER NOT NULL
+            );
+            INSERT INTO questions VALUES (7, 'nan', '0', '1', '2', '3', 2);",
+        )?;
+
+        // Checking does not migrate.
+        assert_eq!(db.check()?, vec![Problem::Outdated]);
+        assert!(has_column(&db.db, "questions", "answers_0")?);
+        assert!(!has_table(&db.db, "answers")?);
+
+        db.repair(&Problem::Outdated)?;
+        assert_eq!(db.check()?, vec![]);
+
+        Ok(())
+    }
 }
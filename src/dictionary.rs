@@ -0,0 +1,29 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::io::Cursor;
+
+use hyphenation::{Load, Standard};
+use once_cell::sync::Lazy;
+
+/// German hyphenation dictionary (`de-1996`), embedded into the binary.
+///
+/// It is used to wrap text in the TUI and to tell soft line breaks from real hyphens in the catalog.
+pub static DICTIONARY: Lazy<Standard> = Lazy::new(|| {
+    let bytes = include_bytes!("../dictionary/de-1996.standard.bincode");
+    let mut curs = Cursor::new(bytes);
+    Standard::any_from_reader(&mut curs).unwrap()
+});
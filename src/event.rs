@@ -14,76 +14,181 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use tracing::{trace, trace_span};
+use tracing::{info, trace, trace_span};
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Report, Result, WrapErr};
 
 use crossterm::event::{self, Event, KeyEvent, MouseEvent};
 
 use std::{
-    sync::mpsc::{self, Receiver, Sender},
-    thread,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
     time::Duration,
 };
 
+/// Interval in which the input thread checks if it should stop.
+const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
 pub enum EventType {
     Key(KeyEvent),
     Mouse(MouseEvent),
     Resize(u16, u16),
+    /// Emitted by [InputEventHandler::next] when no input arrived within the requested timeout.
+    Tick,
+    /// The process received a termination signal (SIGTERM, SIGHUP, SIGINT or SIGQUIT).
+    Terminate(i32),
+    /// Polling or reading from the terminal failed in the input thread.
+    Error(Report),
 }
 
 /// On creation spawns thread capturing key strokes, mouse movements and resizes of window.
 /// Those are then accessable via [channel](Receiver<EventType>) saved in this struct.
+///
+/// On unix a second thread forwards termination signals as [EventType::Terminate],
+/// so that the terminal can be restored instead of being left in raw mode.
+///
+/// The threads are stopped and joined via [InputEventHandler::stop] or when this struct is dropped.
 pub struct InputEventHandler {
     pub sender: Sender<EventType>,
     pub receiver: Receiver<EventType>,
+    stop: Arc<AtomicBool>,
+    threads: Vec<JoinHandle<()>>,
+    #[cfg(unix)]
+    signals_handle: signal_hook::iterator::Handle,
 }
 
 impl InputEventHandler {
     /// Returns struct with channels. Spawns thread capturing key strokes, mouse movements and resizes of window.
-    pub fn new(fps: u64) -> Self {
-        let frametime = 1_000_000_000 / fps;
+    pub fn new() -> Result<Self> {
         let (sender, receiver) = mpsc::channel::<EventType>();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut threads = Vec::new();
 
         {
             let sender = sender.clone();
+            let stop = stop.clone();
 
-            thread::spawn(move || {
+            threads.push(thread::spawn(move || {
                 let _trace_guard =
-                    trace_span!("Key, mouse and resize event polling loop.", frametime).entered();
-                loop {
-                    if event::poll(Duration::from_nanos(frametime))
-                        .expect("Unable to poll for crossterm event.")
-                    {
-                        match event::read().expect("Unable to read crossterm event.") {
-                            Event::Resize(w, h) => sender.send(EventType::Resize(w, h)),
-                            Event::Key(key_event) => {
-                                if key_event.kind == event::KeyEventKind::Press {
-                                    sender.send(EventType::Key(key_event))
-                                } else {
-                                    Ok(())
-                                }
-                            }
-                            Event::Mouse(mouse_event) => sender.send(EventType::Mouse(mouse_event)),
-                            unknown_event => {
-                                trace!(?unknown_event, "Unknown event matched in even handler.");
-                                Ok(())
-                            }
-                        }
-                        .expect("Failed sending in event channel.");
-                    }
+                    trace_span!("Key, mouse and resize event polling loop.").entered();
+                if let Err(err) = poll_input_events(&sender, &stop) {
+                    // If this fails, the receiver is gone anyway.
+                    let _ = sender.send(EventType::Error(err));
                 }
-            });
+            }));
         }
 
-        Self {
+        #[cfg(unix)]
+        let signals_handle = {
+            use signal_hook::consts::{SIGHUP, SIGINT, SIGQUIT, SIGTERM};
+            use signal_hook::iterator::Signals;
+
+            let mut signals = Signals::new([SIGTERM, SIGHUP, SIGINT, SIGQUIT])?;
+            let handle = signals.handle();
+            let sender = sender.clone();
+
+            threads.push(thread::spawn(move || {
+                let _trace_guard = trace_span!("Signal handling loop.").entered();
+                for signal in signals.forever() {
+                    info!(signal, "Received termination signal.");
+                    if sender.send(EventType::Terminate(signal)).is_err() {
+                        break;
+                    }
+                }
+            }));
+
+            handle
+        };
+
+        Ok(Self {
             sender,
             receiver,
+            stop,
+            threads,
+            #[cfg(unix)]
+            signals_handle,
+        })
+    }
+
+    /// Blocks until the next key stroke/mouse event/resize event arrives.
+    ///
+    /// If `timeout` is `Some` and no event arrived in that time, [EventType::Tick] is returned instead.
+    /// An [EventType::Error] of the input thread is returned as error.
+    pub fn next(&self, timeout: Option<Duration>) -> Result<EventType> {
+        let event = match timeout {
+            Some(timeout) => match self.receiver.recv_timeout(timeout) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => EventType::Tick,
+                Err(err) => return Err(err.into()),
+            },
+            None => self.receiver.recv()?,
+        };
+        match event {
+            EventType::Error(err) => Err(err),
+            event => Ok(event),
+        }
+    }
+
+    /// Returns an already queued event without blocking.
+    pub fn try_next(&self) -> Result<Option<EventType>> {
+        match self.receiver.try_recv() {
+            Ok(EventType::Error(err)) => Err(err),
+            Ok(event) => Ok(Some(event)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Signals all threads to stop and waits for them to finish.
+    ///
+    /// The input thread notices this at latest after [POLL_TIMEOUT].
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        #[cfg(unix)]
+        self.signals_handle.close();
+        for thread in self.threads.drain(..) {
+            if thread.join().is_err() {
+                trace!("Event thread panicked.");
+            }
         }
     }
+}
+
+impl Drop for InputEventHandler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
 
-    /// Get next key stroke/mouse event/resize event from channel.
-    pub fn get(&mut self) -> Result<EventType> {
-        Ok(self.receiver.recv()?)
+/// Polls crossterm for events and sends them into the channel until `stop` is set.
+///
+/// Returns without error if the receiving end of the channel was dropped.
+fn poll_input_events(sender: &Sender<EventType>, stop: &AtomicBool) -> Result<()> {
+    while !stop.load(Ordering::Relaxed) {
+        if !event::poll(POLL_TIMEOUT).wrap_err("Unable to poll for crossterm event.")? {
+            continue;
+        }
+        let sent = match event::read().wrap_err("Unable to read crossterm event.")? {
+            Event::Resize(w, h) => sender.send(EventType::Resize(w, h)),
+            Event::Key(key_event) => {
+                if key_event.kind == event::KeyEventKind::Press {
+                    sender.send(EventType::Key(key_event))
+                } else {
+                    Ok(())
+                }
+            }
+            Event::Mouse(mouse_event) => sender.send(EventType::Mouse(mouse_event)),
+            unknown_event => {
+                trace!(?unknown_event, "Unknown event matched in even handler.");
+                Ok(())
+            }
+        };
+        if sent.is_err() {
+            break;
+        }
     }
+    Ok(())
 }
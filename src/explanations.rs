@@ -0,0 +1,91 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+/// File with explanations for the questions of one catalog.
+///
+/// Explanations are matched with questions by the key of the catalog (e.g. `UBI`) and the question number.
+///
+/// ```json
+/// {
+///   "katalog": "UBI",
+///   "erklaerungen": [
+///     { "frage": 12, "text": "Siehe § 3 Abs. 2 RheinSchPV." }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExplanationFile {
+    #[serde(rename = "katalog")]
+    pub catalog: String,
+    #[serde(rename = "erklaerungen")]
+    pub explanations: Vec<Explanation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Explanation {
+    /// Number of the question in the catalog.
+    #[serde(rename = "frage")]
+    pub number: usize,
+    pub text: String,
+}
+
+/// Reads an [ExplanationFile] in json format from `path`.
+pub fn read_explanation_file(path: PathBuf) -> Result<ExplanationFile> {
+    let s = read_to_string(&path)?;
+    parse_explanation_file(&s)
+        .wrap_err_with(|| format!("Ungültige Erklärungsdatei {}", path.display()))
+}
+
+fn parse_explanation_file(s: &str) -> Result<ExplanationFile> {
+    Ok(serde_json::from_str(s)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_explanation_file() -> Result<()> {
+        let file = parse_explanation_file(
+            r#"{
+                "katalog": "SRC",
+                "erklaerungen": [
+                    { "frage": 1, "text": "Kanal 16 ist der Not- und Anrufkanal." },
+                    { "frage": 2, "text": "Zeile 1\nZeile 2" }
+                ]
+            }"#,
+        )?;
+
+        assert_eq!(file.catalog, "SRC");
+        assert_eq!(
+            file.explanations[1],
+            Explanation {
+                number: 2,
+                text: "Zeile 1\nZeile 2".to_owned()
+            }
+        );
+        assert!(parse_explanation_file(r#"{ "katalog": "SRC" }"#).is_err());
+
+        Ok(())
+    }
+}
@@ -27,6 +27,8 @@ pub struct FpsTimer {
 /// 1. Save [Instant].
 /// 2. Main thread is executed.
 /// 3. Timout is called: Checks if fps limit is exceeded and if not, lets thread sleep for the remaining time.
+///
+/// Frames are only drawn on events, so this is merely an upper bound for bursts of input.
 impl FpsTimer {
     /// Returns [FpsTimer] where fps limit is saved as frametime ([Duration]).
     pub fn new(fps: u64) -> Self {
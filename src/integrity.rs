@@ -0,0 +1,148 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::fmt;
+
+use color_eyre::eyre::Report;
+use rusqlite::ErrorCode;
+
+use crate::db::TOTAL_COUNT_TRIES_PER_QUESTION;
+
+/// How many ids of affected questions are listed in the description of a [Problem].
+const LISTED_IDS: usize = 10;
+
+/// Problem in the database found by [DB::check](crate::db::DB::check).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Messages of SQLite's `integrity_check`, e.g. broken indices.
+    Integrity(Vec<String>),
+    /// Tables or columns of an older version, which [DB::new](crate::db::DB::new) would migrate.
+    Outdated,
+    /// Rows of `table` (by rowid), whose question does not exist anymore.
+    Orphans { table: String, rowids: Vec<i64> },
+    /// Questions whose `correctly_answered` is greater than [TOTAL_COUNT_TRIES_PER_QUESTION] or negative.
+    ProgressOutOfRange(Vec<usize>),
+    /// Questions with an empty text or answer, also after corrections.
+    EmptyText(Vec<usize>),
+    /// Questions with less than two answers or without a right one.
+    IncompleteAnswers(Vec<usize>),
+}
+
+impl Problem {
+    /// Whether [DB::repair](crate::db::DB::repair) can fix the problem without losing data the user entered.
+    ///
+    /// Of the [Problem::Integrity] messages only broken indices can be fixed, by rebuilding them.
+    pub fn is_repairable(&self) -> bool {
+        match self {
+            Problem::Integrity(messages) => messages.iter().all(|m| m.contains(" index ")),
+            Problem::Outdated | Problem::Orphans { .. } | Problem::ProgressOutOfRange(_) => true,
+            Problem::EmptyText(_) | Problem::IncompleteAnswers(_) => false,
+        }
+    }
+
+    /// What the user can do about the problem.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Problem::Integrity(_) if self.is_repairable() => "Die Indizes werden neu aufgebaut.",
+            Problem::Integrity(_) => "Benennen Sie die Datenbank um und laden Sie den Katalog neu. Eigene Fragen und Korrekturen exportieren Sie vorher, soweit möglich.",
+            Problem::Outdated => "Die Tabellen werden auf den aktuellen Stand gebracht.",
+            Problem::Orphans { .. } => "Die verwaisten Einträge werden gelöscht.",
+            Problem::ProgressOutOfRange(_) => "Der Fortschritt wird auf den gültigen Bereich begrenzt.",
+            Problem::IncompleteAnswers(_) => {
+                "Laden Sie den Katalog neu (ubilerntui lade) oder bearbeiten Sie die eigene Frage (ubilerntui frage bearbeite)."
+            }
+            Problem::EmptyText(_) => {
+                "Korrigieren Sie den Text nach dem Beantworten der Frage mit (k) oder laden Sie den Katalog neu."
+            }
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (description, ids) = match self {
+            Problem::Integrity(messages) => {
+                return write!(f, "Beschädigte Datenbank: {}", messages.join("; "));
+            }
+            Problem::Outdated => {
+                return write!(f, "Die Datenbank stammt von einer älteren Version")
+            }
+            Problem::Orphans { table, rowids } => {
+                return write!(
+                    f,
+                    "{} Einträge in {table} gehören zu keiner Frage",
+                    rowids.len()
+                );
+            }
+            Problem::ProgressOutOfRange(ids) => (
+                format!("Fortschritt außerhalb von 0 bis {TOTAL_COUNT_TRIES_PER_QUESTION}"),
+                ids,
+            ),
+            Problem::EmptyText(ids) => ("Frage oder Antwort ohne Text".to_owned(), ids),
+            Problem::IncompleteAnswers(ids) => (
+                "Weniger als zwei Antworten oder keine richtige".to_owned(),
+                ids,
+            ),
+        };
+        let mut listed: Vec<String> = ids.iter().take(LISTED_IDS).map(usize::to_string).collect();
+        if ids.len() > LISTED_IDS {
+            listed.push("…".to_owned());
+        }
+        write!(
+            f,
+            "{description}: {} Frage(n) ({})",
+            ids.len(),
+            listed.join(", ")
+        )
+    }
+}
+
+/// Checks if `err` was caused by a corrupted database or a file, which is no database.
+pub fn is_corruption(err: &Report) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<rusqlite::Error>()
+            .and_then(rusqlite::Error::sqlite_error_code)
+            .is_some_and(|code| {
+                matches!(code, ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_problem_description() {
+        let problem = Problem::ProgressOutOfRange((1..=12).collect());
+        assert_eq!(
+            problem.to_string(),
+            "Fortschritt außerhalb von 0 bis 3: 12 Frage(n) (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, …)"
+        );
+        assert!(problem.is_repairable());
+        assert!(!Problem::EmptyText(vec![1]).is_repairable());
+        assert!(Problem::Integrity(vec![
+            "row 3 missing from index sqlite_autoindex_answers_1".to_owned()
+        ])
+        .is_repairable());
+        assert!(!Problem::Integrity(vec![
+            "Page 4: btreeInitPage() returns error code 11".to_owned()
+        ])
+        .is_repairable());
+    }
+}
@@ -0,0 +1,200 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::fmt;
+use std::fs::write;
+use std::path::Path;
+
+use color_eyre::eyre::Result;
+
+use crate::pdfparser::Catalog;
+
+/// Maximum length of [ParseIssue::snippet] in bytes.
+const SNIPPET_LEN: usize = 300;
+
+/// What went wrong while parsing a question.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// The regex failed (e.g. its backtrack limit was hit), parsing continued at the next line.
+    Regex(String),
+    /// The number of the question could not be read.
+    InvalidNumber,
+    /// A question needs at least two answers.
+    TooFewAnswers(usize),
+    /// A question with this number was parsed before, the later one is skipped.
+    DuplicateNumber,
+    /// A line starts like a question, but no question was parsed from it.
+    Unmatched,
+    /// The number in brackets (e.g. `[12]`) differs from the number in front of the question.
+    NumberMismatch(usize),
+    /// The question or one of its answers has no text.
+    EmptyText,
+}
+
+impl IssueKind {
+    /// Whether the question was skipped. Otherwise it was imported and should be checked.
+    pub fn is_error(&self) -> bool {
+        !matches!(self, IssueKind::NumberMismatch(_) | IssueKind::EmptyText)
+    }
+}
+
+impl fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IssueKind::Regex(err) => write!(f, "Fehler beim Erkennen der Fragen: {err}"),
+            IssueKind::InvalidNumber => write!(f, "Die Nummer der Frage ist ungültig"),
+            IssueKind::TooFewAnswers(count) => {
+                write!(
+                    f,
+                    "Nur {count} Antwort(en) erkannt, mindestens 2 sind nötig"
+                )
+            }
+            IssueKind::DuplicateNumber => write!(f, "Die Nummer wurde bereits vergeben"),
+            IssueKind::Unmatched => write!(f, "Beginnt wie eine Frage, wurde aber nicht erkannt"),
+            IssueKind::NumberMismatch(number) => {
+                write!(f, "Die Nummer in Klammern lautet [{number}]")
+            }
+            IssueKind::EmptyText => write!(f, "Die Frage oder eine Antwort ist leer"),
+        }
+    }
+}
+
+/// Problem while parsing a question of a catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseIssue {
+    pub kind: IssueKind,
+    /// Byte position in the extracted text.
+    pub offset: usize,
+    pub page: Option<usize>,
+    /// Number of the question, if it could be read.
+    pub number: Option<usize>,
+    /// Extracted text around [offset](ParseIssue::offset).
+    pub snippet: String,
+}
+
+impl ParseIssue {
+    /// Creates an issue with the text of `s` between `start` and `end` as snippet.
+    pub fn new(kind: IssueKind, s: &str, start: usize, end: usize) -> Self {
+        ParseIssue {
+            kind,
+            offset: start,
+            page: None,
+            number: None,
+            snippet: snippet(s, start, end),
+        }
+    }
+
+    pub fn with_number(mut self, number: usize) -> Self {
+        self.number = Some(number);
+        self
+    }
+
+    /// Sets the page by the byte positions of the pages, see [page_of].
+    pub fn with_page(mut self, page_starts: &[usize]) -> Self {
+        self.page = page_of(page_starts, self.offset);
+        self
+    }
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bei Byte {}",
+            if self.kind.is_error() {
+                "Fehler"
+            } else {
+                "Warnung"
+            },
+            self.offset
+        )?;
+        if let Some(page) = self.page {
+            write!(f, " (Seite {page})")?;
+        }
+        if let Some(number) = self.number {
+            write!(f, ", Frage {number}")?;
+        }
+        writeln!(f, ": {}", self.kind)?;
+        for line in self.snippet.lines() {
+            writeln!(f, "    | {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the number of the page `offset` lies on, if the byte positions of the pages are given by `page_starts`.
+pub fn page_of(page_starts: &[usize], offset: usize) -> Option<usize> {
+    (!page_starts.is_empty())
+        .then(|| page_starts.partition_point(|&page_start| page_start <= offset))
+}
+
+/// Returns the trimmed text of `s` between `start` and `end`, cut to [SNIPPET_LEN] bytes.
+fn snippet(s: &str, start: usize, end: usize) -> String {
+    let mut end = end.min(start + SNIPPET_LEN).min(s.len());
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s.get(start..end).unwrap_or_default().trim().to_owned()
+}
+
+/// Writes the issues of `catalog` parsed out of `source` as text file to `path`.
+pub fn write_report(path: &Path, source: &str, catalog: &Catalog) -> Result<()> {
+    let errors = catalog
+        .issues
+        .iter()
+        .filter(|issue| issue.kind.is_error())
+        .count();
+    let mut report = format!(
+        "Bericht zum Laden von {source}\n\
+        {} Fragen geladen, {errors} Fehler (Fragen übersprungen), {} Warnungen (Fragen geladen, bitte prüfen).\n\n",
+        catalog.questions.len(),
+        catalog.issues.len() - errors,
+    );
+    for issue in &catalog.issues {
+        report.push_str(&issue.to_string());
+        report.push('\n');
+    }
+    write(path, report)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_issue() {
+        let s = "Vorwort\n\n12.   Frage ohne Antworten\n\n13. Nächste";
+        let issue = ParseIssue::new(IssueKind::Unmatched, s, 9, s.find("13.").unwrap())
+            .with_number(12)
+            .with_page(&[0, 20]);
+
+        assert_eq!(issue.snippet, "12.   Frage ohne Antworten");
+        assert_eq!(issue.page, Some(1));
+        assert_eq!(
+            issue.to_string(),
+            "Fehler bei Byte 9 (Seite 1), Frage 12: Beginnt wie eine Frage, wurde aber nicht erkannt\n    | 12.   Frage ohne Antworten\n"
+        );
+    }
+
+    #[test]
+    fn test_snippet_char_boundary() {
+        let s = "ä".repeat(SNIPPET_LEN);
+        assert_eq!(snippet(&s, 0, s.len()).len(), SNIPPET_LEN);
+        assert_eq!(snippet(&s, 1, 3), "");
+    }
+}
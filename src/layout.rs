@@ -0,0 +1,551 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::collections::{HashMap, HashSet};
+use std::fs::read;
+use std::path::PathBuf;
+
+use color_eyre::eyre::Result;
+use fancy_regex::Regex;
+use once_cell::sync::Lazy;
+use pdf_extract::{output_doc, Document, MediaBox, OutputDev, OutputError, Transform};
+
+use crate::app::QuestionAnswer;
+use crate::cleantext::{clean_text, normalize};
+use crate::issues::{IssueKind, ParseIssue};
+use crate::pdfparser::{extract_edition, extract_title, Catalog, CatalogFormat};
+use crate::selection::is_roman_numeral;
+
+/// Line of text on a page of a PDF with its position.
+///
+/// `x` and `y` are the position of the first glyph in points, `y` measured from the top of the page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLine {
+    pub page: usize,
+    pub x: f64,
+    pub y: f64,
+    pub font_size: f64,
+    pub text: String,
+}
+
+/// Reads the PDF at `path` as [TextLine]s in order of the content of each page.
+pub fn read_pdf_lines(path: PathBuf) -> Result<Vec<TextLine>> {
+    extract_pdf_lines(&read(path)?)
+}
+
+/// Extracts the [TextLine]s of the PDF in `bytes`, see [read_pdf_lines].
+pub fn extract_pdf_lines(bytes: &[u8]) -> Result<Vec<TextLine>> {
+    let mut doc = Document::load_mem(bytes)?;
+    if doc.is_encrypted() {
+        doc.decrypt("")?;
+    }
+    let mut output = LineOutput::default();
+    output_doc(&doc, &mut output)?;
+    Ok(output.lines)
+}
+
+/// [OutputDev] collecting glyphs into [TextLine]s.
+///
+/// A glyph starts a new line if it is not on the height of the current line or if it lies left of the end of the line.
+#[derive(Default)]
+struct LineOutput {
+    lines: Vec<TextLine>,
+    current: Option<TextLine>,
+    page: usize,
+    page_height: f64,
+    /// Horizontal end of the last glyph.
+    last_end: f64,
+}
+
+impl LineOutput {
+    fn finish_line(&mut self) {
+        if let Some(line) = self.current.take() {
+            if !line.text.trim().is_empty() {
+                self.lines.push(line);
+            }
+        }
+    }
+}
+
+impl OutputDev for LineOutput {
+    fn begin_page(
+        &mut self,
+        page_num: u32,
+        media_box: &MediaBox,
+        _: Option<(f64, f64, f64, f64)>,
+    ) -> Result<(), OutputError> {
+        self.page = page_num as usize;
+        self.page_height = media_box.ury - media_box.lly;
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), OutputError> {
+        self.finish_line();
+        Ok(())
+    }
+
+    fn output_character(
+        &mut self,
+        trm: &Transform,
+        width: f64,
+        _spacing: f64,
+        font_size: f64,
+        char: &str,
+    ) -> Result<(), OutputError> {
+        // Side of the square with the area of the transformed font size, like `pdf_extract` does.
+        let size_x = font_size * (trm.m11 + trm.m21);
+        let size_y = font_size * (trm.m12 + trm.m22);
+        let size = (size_x * size_y).abs().sqrt();
+        let (x, y) = (trm.m31, self.page_height - trm.m32);
+
+        let same_line = self.current.as_ref().is_some_and(|line| {
+            (y - line.y).abs() <= size * 0.5 && x >= self.last_end - size * 0.5
+        });
+        if same_line {
+            let line = self.current.as_mut().expect("same line");
+            if x > self.last_end + size * 0.1 {
+                line.text.push(' ');
+            }
+            line.text.push_str(char);
+        } else {
+            self.finish_line();
+            self.current = Some(TextLine {
+                page: self.page,
+                x,
+                y,
+                font_size: size,
+                text: char.to_owned(),
+            });
+        }
+        self.last_end = x + width * size;
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+/// Part of a question the following lines belong to.
+enum Field {
+    Question,
+    Answer,
+}
+
+/// Question, whose lines are being read, starting at the line with index `line`.
+struct Pending {
+    q: QuestionAnswer,
+    answers: Vec<String>,
+    line: usize,
+}
+
+/// Questions and issues read out of `lines`.
+///
+/// `text` are the lines joined by line breaks, with the byte position of every line in `offsets`.
+struct Parsed<'a> {
+    lines: &'a [TextLine],
+    text: String,
+    offsets: Vec<usize>,
+    questions: Vec<QuestionAnswer>,
+    issues: Vec<ParseIssue>,
+    numbers: HashSet<usize>,
+}
+
+impl<'a> Parsed<'a> {
+    fn new(lines: &'a [TextLine]) -> Self {
+        let mut text = String::new();
+        let mut offsets = Vec::with_capacity(lines.len() + 1);
+        for line in lines {
+            offsets.push(text.len());
+            text.push_str(&line.text);
+            text.push('\n');
+        }
+        offsets.push(text.len());
+        Parsed {
+            lines,
+            text,
+            offsets,
+            questions: vec![],
+            issues: vec![],
+            numbers: HashSet::new(),
+        }
+    }
+
+    /// Saves an issue for the lines from index `start` until `end`.
+    fn issue(&mut self, kind: IssueKind, start: usize, end: usize, number: Option<usize>) {
+        let mut issue = ParseIssue::new(kind, &self.text, self.offsets[start], self.offsets[end]);
+        issue.page = Some(self.lines[start].page);
+        issue.number = number;
+        self.issues.push(issue);
+    }
+
+    /// Adds `pending` to the questions, if it has at least two answers and a new number.
+    /// Its lines end before the line with index `end`.
+    fn finish(&mut self, pending: Option<Pending>, end: usize) {
+        let Some(Pending {
+            mut q,
+            answers,
+            line,
+        }) = pending
+        else {
+            return;
+        };
+        if answers.len() < 2 {
+            self.issue(
+                IssueKind::TooFewAnswers(answers.len()),
+                line,
+                end,
+                Some(q.id),
+            );
+            return;
+        }
+        if !self.numbers.insert(q.id) {
+            self.issue(IssueKind::DuplicateNumber, line, end, Some(q.id));
+            return;
+        }
+        q.possible_answers = answers.iter().map(|answer| clean_text(answer)).collect();
+        q.order = (0..q.possible_answers.len()).collect();
+        if q.possible_answers.iter().any(String::is_empty) {
+            self.issue(IssueKind::EmptyText, line, end, Some(q.id));
+        }
+        self.questions.push(q);
+    }
+}
+
+/// Parses the questions of a catalog out of the positioned lines of its PDF.
+///
+/// Unlike [parse_pdf](crate::pdfparser::parse_pdf) it does not depend on the whitespace of the extracted text:
+/// - Running headers and footers are lines repeated on most pages (ignoring digits, e.g. page numbers).
+/// - Questions start with their number (e.g. `12.`) in the column, where most numbers are.
+///   Answers start with a label (`a)` for UBI, `1)` for SRC). Every other line continues the question or the answer before.
+/// - Section headers are roman numerals in the column of the questions, the title may be on the next line.
+///
+/// The `[id]` in the text of a question is removed.
+/// Questions with less than two answers or an already used number are skipped and saved in `issues`.
+pub fn parse_layout(lines: &[TextLine]) -> Result<Catalog> {
+    static REG_QUESTION: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(?P<id>[0-9]{1,3})\.(?:\s+(?P<text>.*))?$").unwrap());
+    static REG_ANSWER: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(?P<label>[a-z]|[0-9]{1,2})\)\s*(?P<text>.*)$").unwrap());
+    static REG_SECTION: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(?P<numeral>[IVX]+)\.(?:\s+(?P<title>.*))?$").unwrap());
+    static REG_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s*\[[0-9]{1,3}\]").unwrap());
+
+    let lines = remove_running_lines(lines);
+    let paragraphs = normalize(&paragraph_text(&lines));
+    let lines: Vec<TextLine> = lines
+        .into_iter()
+        .map(|line| TextLine {
+            text: normalize(&line.text).trim().to_owned(),
+            ..line
+        })
+        .collect();
+    let column = question_column(&lines, &REG_QUESTION);
+    let in_column = |line: &TextLine| column.is_some_and(|x| (line.x - x).abs() <= line.font_size);
+
+    let mut parsed = Parsed::new(&lines);
+    let mut current: Option<Pending> = None;
+    let mut field = Field::Question;
+    let mut section: Option<String> = None;
+    let mut letter_labels = 0;
+    let mut number_labels = 0;
+    let mut lines_iter = lines.iter().enumerate();
+
+    while let Some((i, line)) = lines_iter.next() {
+        let text = line.text.as_str();
+        let section_caps = REG_SECTION.captures(text).ok().flatten();
+        let question_caps = REG_QUESTION.captures(text).ok().flatten();
+        let answer_caps = REG_ANSWER.captures(text).ok().flatten();
+
+        if let Some(caps) =
+            section_caps.filter(|caps| in_column(line) && is_roman_numeral(&caps["numeral"]))
+        {
+            parsed.finish(current.take(), i);
+            let title = match caps.name("title") {
+                Some(title) => title.as_str().to_owned(),
+                None => lines_iter
+                    .next()
+                    .map(|(_, next)| next.text.clone())
+                    .unwrap_or_default(),
+            };
+            section = Some(format!("{}. {}", &caps["numeral"], clean_text(&title)));
+        } else if let Some(caps) = question_caps.filter(|_| in_column(line)) {
+            parsed.finish(current.take(), i);
+            let Ok(id) = caps["id"].parse() else {
+                parsed.issue(IssueKind::InvalidNumber, i, i + 1, None);
+                continue;
+            };
+            let mut q = QuestionAnswer::new(id, "", vec![], vec![0]);
+            q.section = section.clone();
+            q.page = Some(line.page);
+            q.question = caps.name("text").map_or("", |t| t.as_str()).to_owned();
+            current = Some(Pending {
+                q,
+                answers: vec![],
+                line: i,
+            });
+            field = Field::Question;
+        } else if let (Some(caps), Some(pending)) = (answer_caps, current.as_mut()) {
+            if caps["label"].starts_with(|c: char| c.is_ascii_digit()) {
+                number_labels += 1;
+            } else {
+                letter_labels += 1;
+            }
+            pending.answers.push(caps["text"].to_owned());
+            field = Field::Answer;
+        } else if let Some(pending) = current.as_mut() {
+            let continued = match field {
+                Field::Question => &mut pending.q.question,
+                Field::Answer => pending.answers.last_mut().expect("answer started"),
+            };
+            continued.push('\n');
+            continued.push_str(text);
+        }
+    }
+    parsed.finish(current.take(), lines.len());
+
+    let mut questions = parsed.questions;
+    for q in questions.iter_mut() {
+        q.question = clean_text(&REG_ID.replace_all(&q.question, ""));
+    }
+
+    Ok(Catalog {
+        format: if number_labels > letter_labels {
+            CatalogFormat::Src
+        } else {
+            CatalogFormat::Ubi
+        },
+        questions,
+        issues: parsed.issues,
+        title: extract_title(&paragraphs),
+        edition: extract_edition(&paragraphs),
+    })
+}
+
+/// Returns the text of `lines` with a blank line between paragraphs,
+/// which are separated by a gap of more than half a line, a change of the font size or a new page.
+fn paragraph_text(lines: &[TextLine]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&TextLine> = None;
+    for line in lines {
+        if let Some(previous) = previous {
+            let new_paragraph = line.page != previous.page
+                || line.y - previous.y > previous.font_size * 1.5
+                || (line.font_size - previous.font_size).abs() > 0.5;
+            text.push_str(if new_paragraph { "\n\n" } else { "\n" });
+        }
+        text.push_str(&line.text);
+        previous = Some(line);
+    }
+    text
+}
+
+/// Returns the lines without running headers and footers,
+/// which are lines repeated on more than half of the pages, ignoring digits (e.g. page numbers).
+fn remove_running_lines(lines: &[TextLine]) -> Vec<TextLine> {
+    let key = |line: &TextLine| -> String {
+        line.text
+            .chars()
+            .filter(|c| !c.is_ascii_digit() && !c.is_whitespace())
+            .collect()
+    };
+    let page_count = lines
+        .iter()
+        .map(|line| line.page)
+        .collect::<HashSet<_>>()
+        .len();
+    let mut pages_of_key: HashMap<String, HashSet<usize>> = HashMap::new();
+    for line in lines {
+        pages_of_key.entry(key(line)).or_default().insert(line.page);
+    }
+
+    lines
+        .iter()
+        .filter(|line| page_count < 3 || pages_of_key[&key(line)].len() * 2 <= page_count)
+        .cloned()
+        .collect()
+}
+
+/// Returns the horizontal position most lines starting with a question number have.
+fn question_column(lines: &[TextLine], reg_question: &Regex) -> Option<f64> {
+    let mut counts: HashMap<i64, usize> = HashMap::new();
+    for line in lines {
+        if reg_question.is_match(&line.text).unwrap_or(false) {
+            *counts.entry(line.x.round() as i64).or_default() += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(x, count)| (count, -x))
+        .map(|(x, _)| x as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Builds lines of `page` from `(x, text)`, each 12 points below the one before.
+    fn page(page: usize, lines: &[(f64, &str)]) -> Vec<TextLine> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, text))| TextLine {
+                page,
+                x,
+                y: 50.0 + 12.0 * i as f64,
+                font_size: 10.0,
+                text: text.to_owned(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_layout() -> Result<()> {
+        let mut lines = page(
+            1,
+            &[
+                (200.0, "Gesamtfragenkatalog UBI Seite 1"),
+                (50.0, "Fragenkatalog für das UBI"),
+                (50.0, "I."),
+                (50.0, "Allgemeines"),
+                (50.0, "1.   Was ist richtig?   [1]"),
+                (70.0, "a)   eins"),
+                (70.0, "b)   zwei"),
+                (50.0, "2.   Welche Kanäle sind für den"),
+                (70.0, "Schiff-Schiff-Verkehr bestimmt?   [2]"),
+                (70.0, "a)   Kanal 10 und"),
+                (70.0, "13"),
+            ],
+        );
+        lines[1].font_size = 14.0;
+        lines.extend(page(
+            2,
+            &[
+                (200.0, "Gesamtfragenkatalog UBI Seite 2"),
+                (70.0, "b)   Kanal 16"),
+                (70.0, "c)   Kanal 22"),
+                (50.0, "II. Betriebsverfahren"),
+                (50.0, "3. Frage?"),
+                (70.0, "a) ja"),
+                (70.0, "b) nein"),
+                (70.0, "4. kein Fragenanfang, da eingerückt"),
+            ],
+        ));
+        lines.extend(page(3, &[(200.0, "Gesamtfragenkatalog UBI Seite 3")]));
+
+        let catalog = parse_layout(&lines)?;
+        assert_eq!(catalog.format, CatalogFormat::Ubi);
+        assert_eq!(catalog.title.as_deref(), Some("Fragenkatalog für das UBI"));
+
+        let mut expected = vec![
+            QuestionAnswer::new(1, "Was ist richtig?", vec!["eins", "zwei"], vec![0]),
+            QuestionAnswer::new(
+                2,
+                "Welche Kanäle sind für den Schiff-Schiff-Verkehr bestimmt?",
+                vec!["Kanal 10 und 13", "Kanal 16", "Kanal 22"],
+                vec![0],
+            ),
+            QuestionAnswer::new(
+                3,
+                "Frage?",
+                vec!["ja", "nein 4. kein Fragenanfang, da eingerückt"],
+                vec![0],
+            ),
+        ];
+        for (q, (section, page)) in expected.iter_mut().zip([
+            ("I. Allgemeines", 1),
+            ("I. Allgemeines", 1),
+            ("II. Betriebsverfahren", 2),
+        ]) {
+            q.section = Some(section.to_owned());
+            q.page = Some(page);
+        }
+        assert_eq!(catalog.questions, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_src_labels() -> Result<()> {
+        let lines = page(
+            1,
+            &[
+                (50.0, "12. Frage [12]"),
+                (50.0, "1) richtig"),
+                (50.0, "2) falsch"),
+                (50.0, "13. Nur eine Antwort [13]"),
+                (50.0, "1) richtig"),
+            ],
+        );
+
+        let catalog = parse_layout(&lines)?;
+        assert_eq!(catalog.format, CatalogFormat::Src);
+        assert_eq!(catalog.questions.len(), 1);
+        assert_eq!(
+            catalog.questions[0].possible_answers,
+            vec!["richtig", "falsch"]
+        );
+
+        let issue = &catalog.issues[0];
+        assert_eq!(catalog.issues.len(), 1);
+        assert_eq!(issue.kind, IssueKind::TooFewAnswers(1));
+        assert_eq!(issue.number, Some(13));
+        assert_eq!(issue.snippet, "13. Nur eine Antwort [13]\n1) richtig");
+        assert_eq!(
+            issue.offset,
+            "12. Frage [12]\n1) richtig\n2) falsch\n".len()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_output() -> Result<(), OutputError> {
+        let media_box = MediaBox {
+            llx: 0.0,
+            lly: 0.0,
+            urx: 600.0,
+            ury: 800.0,
+        };
+        let glyph = |x: f64, y: f64| Transform::row_major(1.0, 0.0, 0.0, 1.0, x, y);
+
+        let mut output = LineOutput::default();
+        output.begin_page(1, &media_box, None)?;
+        output.output_character(&glyph(50.0, 700.0), 0.5, 0.0, 10.0, "a")?;
+        output.output_character(&glyph(55.0, 700.0), 0.5, 0.0, 10.0, ")")?;
+        output.output_character(&glyph(70.0, 700.0), 0.5, 0.0, 10.0, "x")?;
+        output.output_character(&glyph(50.0, 688.0), 0.5, 0.0, 10.0, "b")?;
+        output.end_page()?;
+
+        let texts: Vec<(&str, f64)> = output
+            .lines
+            .iter()
+            .map(|line| (line.text.as_str(), line.y))
+            .collect();
+        assert_eq!(texts, vec![("a) x", 100.0), ("b", 112.0)]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::fs::{read_to_string, File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::Path;
+use std::process;
+
+use chrono::Local;
+use color_eyre::eyre::Result;
+
+/// Lock of the database held by the running instance of ubilerntui.
+///
+/// The lock is released, when it is dropped or the process ends, even after a crash.
+/// The locked file holds the process id and start time of the instance for [InstanceLock::holder].
+#[derive(Debug)]
+pub struct InstanceLock {
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Locks the file at `path`, which is created if missing.
+    ///
+    /// Returns `None`, if another instance holds the lock.
+    pub fn try_acquire(path: &Path) -> Result<Option<Self>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(err)) => return Err(err.into()),
+        }
+        file.set_len(0)?;
+        write!(
+            file,
+            "PID {}, seit {}",
+            process::id(),
+            Local::now().format("%H:%M:%S")
+        )?;
+        Ok(Some(InstanceLock { _file: file }))
+    }
+
+    /// Describes the instance holding the lock at `path`, e.g. `PID 1234, seit 14:02:11`.
+    ///
+    /// Returns `None`, if the file can not be read (e.g. on windows while it is locked).
+    pub fn holder(path: &Path) -> Option<String> {
+        read_to_string(path)
+            .ok()
+            .filter(|holder| !holder.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instance_lock() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("ubilerndb.lock");
+
+        let lock = InstanceLock::try_acquire(&path)?;
+        assert!(lock.is_some());
+        assert!(InstanceLock::try_acquire(&path)?.is_none());
+
+        drop(lock);
+        assert!(InstanceLock::try_acquire(&path)?.is_some());
+
+        Ok(())
+    }
+}
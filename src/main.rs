@@ -4,6 +4,7 @@
 //! and to train each question.
 
 use std::env;
+use std::fs::write;
 use std::io;
 use std::io::Write;
 use std::path::PathBuf;
@@ -25,10 +26,12 @@ use std::path::PathBuf;
  */
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{OptionExt, Result};
 use tracing::{info, trace, trace_span};
 
+use clap::{parser::ValueSource, ArgMatches};
 use colored::*;
 
 use license_fetcher::read_package_list_from_out_dir;
@@ -43,7 +46,7 @@ pub mod hooks;
 use hooks::eyre_term_exit_hook;
 
 pub mod app;
-use app::App;
+use app::{AnswerMode, App, Scoring};
 
 pub mod event;
 
@@ -53,19 +56,64 @@ pub mod ui;
 
 pub mod fpslimiter;
 
+pub mod lock;
+use lock::InstanceLock;
+
 pub mod db;
 use db::DB;
 
 pub mod fs;
+use fs::get_local_dir;
+
+pub mod dictionary;
+
+pub mod cleantext;
+
+pub mod integrity;
+use integrity::{is_corruption, Problem};
+
+pub mod issues;
+use issues::write_report;
+
+pub mod metadata;
+use metadata::CatalogInfo;
 
 pub mod pdfparser;
-use pdfparser::{parse_pdf, read_pdf_to_string};
+use pdfparser::{parse_pdf_pages, read_pdf_pages, Extraction, TextSource, PAGE_SEPARATOR};
+
+pub mod layout;
+use layout::{extract_pdf_lines, parse_layout};
+
+pub mod textinput;
+
+pub mod explanations;
+use explanations::read_explanation_file;
+
+pub mod overrides;
+use overrides::{read_override_file, write_override_file, OverrideFile};
+
+pub mod custom;
+use custom::{custom_id, custom_number, read_deck_file, write_deck_file, CustomQuestion, DeckFile};
+
+pub mod selection;
+use selection::{Filter, Order, Pool, Progress, Selection};
+
+pub mod session;
+use session::Session;
+
+pub mod stats;
+
+pub mod menu;
 
 pub mod argparsing;
-use argparsing::commands_and_flags;
+use argparsing::{commands_and_flags, is_read_only, LEARN_FLAGS};
 
 const LOG_DIR_NAME: &str = "logs";
 const DB_DIR_NAME: &str = "db";
+/// File name of the report of `lade` about questions, which could not be parsed.
+const REPORT_NAME: &str = "lade-bericht.txt";
+/// File name of the [InstanceLock], next to the database.
+const LOCK_NAME: &str = "ubilerndb.lock";
 const FPS: u64 = 120;
 
 /// Entry point of program.
@@ -84,35 +132,229 @@ fn main() -> Result<()> {
           "program_and_env_info"
     );
 
-    let db = DB::new(DB_DIR_NAME)?;
-
     let mut commands = commands_and_flags();
     let matches = commands.clone().get_matches();
 
+    // Held until the end of main, so only one instance writes to the database.
+    let lock_path = get_local_dir(DB_DIR_NAME)?.join(LOCK_NAME);
+    let lock = InstanceLock::try_acquire(&lock_path)?;
+    let db = match lock {
+        // Opened without migrating, so a broken database can still be checked.
+        Some(_) if matches.subcommand_name() == Some("pruefe") => DB::new_unchecked(DB_DIR_NAME)?,
+        Some(_) => DB::new(DB_DIR_NAME)?,
+        None if is_read_only(&matches) => DB::new_read_only(DB_DIR_NAME)?,
+        None => {
+            let holder = InstanceLock::holder(&lock_path)
+                .map(|holder| format!(" ({holder})"))
+                .unwrap_or_default();
+            println!(
+                "{}",
+                format!(
+                    "ubilerntui läuft bereits in einem anderen Terminal{holder}. \
+                    Bitte beenden Sie es zuerst, damit der Fortschritt nicht durcheinandergerät. \
+                    Nur lesende Befehle (zBsp. info) sind währenddessen möglich."
+                )
+                .yellow()
+            );
+            return Ok(());
+        }
+    };
+
     match matches.subcommand() {
         Some(("lade", sub_matches)) => {
+            let path_str = sub_matches.get_one::<String>("PFAD").expect("required");
+            let source = TextSource::from_arg(path_str);
+            let extraction = *sub_matches
+                .get_one::<Extraction>("extraktion")
+                .expect("default");
+            if extraction == Extraction::Layout && !matches!(source, TextSource::Pdf(_)) {
+                println!(
+                    "{}",
+                    "Die Extraktion layout benötigt eine PDF-Datei.".yellow()
+                );
+                return Ok(());
+            }
             if !db.is_empty()? {
+                if source == TextSource::Stdin {
+                    println!(
+                        "{}",
+                        "Beim Lesen von der Standardeingabe kann nicht nachgefragt werden. Bitte löschen Sie zuerst die alten Fragen (ubilerntui loesche fragen).".yellow()
+                    );
+                    return Ok(());
+                }
                 if !yn_inquire("Das Laden wird die alten Daten überschreiben. Trotzdem tun?")? {
                     return Ok(());
                 }
-                db.clear()?;
             }
-            let path_str = sub_matches.get_one::<String>("PFAD").expect("required");
-            let path = PathBuf::from(path_str);
-            let mut count = 0;
-            for q in parse_pdf(read_pdf_to_string(path)?)? {
-                db.insert_tuple(q)?;
-                count += 1;
+            let parse_start = Instant::now();
+            let bytes = source.read()?;
+            let catalog = match (extraction, &source) {
+                (Extraction::Layout, TextSource::Pdf(_)) => {
+                    parse_layout(&extract_pdf_lines(&bytes)?)?
+                }
+                _ => parse_pdf_pages(&source.pages(&bytes)?)?,
+            };
+            let parse_time = parse_start.elapsed();
+            if !catalog.issues.is_empty() {
+                let report_path = match sub_matches.get_one::<String>("bericht") {
+                    Some(report_path) => PathBuf::from(report_path),
+                    None => get_local_dir(DB_DIR_NAME)?.join(REPORT_NAME),
+                };
+                write_report(&report_path, path_str, &catalog)?;
+                let errors = catalog
+                    .issues
+                    .iter()
+                    .filter(|issue| issue.kind.is_error())
+                    .count();
+                println!(
+                    "{}",
+                    format!(
+                        "{errors} Fragen konnten nicht gelesen werden, {} Fragen sollten geprüft werden. Bericht: {}",
+                        catalog.issues.len() - errors,
+                        report_path.display()
+                    )
+                    .yellow()
+                );
             }
+            let count = catalog.questions.len();
             if count == 0 {
                 println!("{}", "Zu wenige Fragen wurden geladen!
                         Bitte öffnen Sie auf github ein Issue mit einem Link zu dem Fragenkatalog, den Sie versucht haben zu laden.".yellow());
             } else {
+                let import_start = Instant::now();
+                let catalog_info = CatalogInfo::new(&catalog, &source.name(), &bytes, extraction);
+                db.import_catalog(&catalog, &catalog_info)?;
+                let import_time = import_start.elapsed();
+                info!(count, ?parse_time, ?import_time, "catalog_imported");
                 let res_msg =
-                    format!("{count} Fragen erfolgreich aus der PDF-Datei geladen. Bitte gleichen Sie ab, ob dies der Anzahl in Ihrem Fragenkatalog entspricht.").green();
+                    format!("{count} Fragen erfolgreich aus der Datei geladen. Bitte gleichen Sie ab, ob dies der Anzahl in Ihrem Fragenkatalog entspricht.").green();
                 println!("{}", res_msg);
+                println!("Gelesen in {parse_time:.1?}, gespeichert in {import_time:.1?}.");
+            }
+        }
+        Some(("info", _)) => match db.catalog_info()? {
+            Some(catalog_info) => {
+                let count = db.count_all_questions()?;
+                let custom_count = db.get_custom_questions(None)?.len();
+                print!("{catalog_info}");
+                println!("{:<20} {}", "Fragen:", count - custom_count);
+                println!("{:<20} {}", "Eigene Fragen:", custom_count);
+            }
+            None if db.is_empty()? => println!(
+                "{}",
+                "Es ist kein Katalog geladen (ubilerntui lade).".yellow()
+            ),
+            None => println!(
+                "{}",
+                "Der Katalog wurde mit einer älteren Version geladen. Bitte laden Sie ihn neu (ubilerntui lade), um mehr zu sehen.".yellow()
+            ),
+        },
+        Some(("pruefe", _)) => loop {
+            let Some(problems) = check_db(&db)? else {
+                return Ok(());
+            };
+            if problems.is_empty() {
+                println!("{}", "Keine Probleme gefunden.".green());
+                break;
+            }
+            for problem in &problems {
+                println!("{}", problem.to_string().yellow());
+                println!("    {}", problem.hint());
+            }
+            let repairable: Vec<_> = problems
+                .iter()
+                .filter(|problem| problem.is_repairable())
+                .collect();
+            if repairable.is_empty()
+                || !yn_inquire(&format!(
+                    "{} von {} Problemen können repariert werden. Jetzt reparieren?",
+                    repairable.len(),
+                    problems.len()
+                ))?
+            {
+                break;
+            }
+            for problem in repairable {
+                db.repair(problem)?;
+            }
+            println!("Repariert. Erneute Prüfung:");
+        },
+        Some(("text", sub_matches)) => match (*sub_matches).subcommand() {
+            Some(("extrahieren", sub_matches)) => {
+                let path_str = sub_matches.get_one::<String>("PFAD").expect("required");
+                let text =
+                    read_pdf_pages(PathBuf::from(path_str))?.join(&PAGE_SEPARATOR.to_string());
+                match sub_matches.get_one::<String>("ausgabe") {
+                    Some(output) => write(output, text)?,
+                    None => io::stdout().write_all(text.as_bytes())?,
+                }
+            }
+            _ => unimplemented!(),
+        },
+        Some(("erklaerungen", sub_matches)) => {
+            let path_str = sub_matches.get_one::<String>("PFAD").expect("required");
+            let file = read_explanation_file(PathBuf::from(path_str))?;
+            for explanation in &file.explanations {
+                db.set_explanation(&file.catalog, explanation.number, explanation.text.trim())?;
+            }
+            println!(
+                "{}",
+                format!(
+                    "{} Erklärungen für den Katalog {} geladen.",
+                    file.explanations.len(),
+                    file.catalog
+                )
+                .green()
+            );
+            match db.catalog()? {
+                Some(catalog) if catalog == file.catalog => {}
+                Some(catalog) => println!(
+                    "{}",
+                    format!("Der geladene Katalog ist {catalog}, die Erklärungen werden erst mit dem Katalog {} angezeigt.", file.catalog).yellow()
+                ),
+                None => println!(
+                    "{}",
+                    "Bitte laden Sie den Katalog neu (ubilerntui lade), damit die Erklärungen angezeigt werden.".yellow()
+                ),
             }
         }
+        Some(("korrekturen", sub_matches)) => match (*sub_matches).subcommand() {
+            Some(("exportiere", sub_matches)) => {
+                let path_str = sub_matches.get_one::<String>("PFAD").expect("required");
+                let Some(catalog) = db.catalog()? else {
+                    println!(
+                        "{}",
+                        "Bitte laden Sie den Katalog neu (ubilerntui lade), um Korrekturen zu exportieren.".yellow()
+                    );
+                    return Ok(());
+                };
+                let overrides = db.get_overrides(&catalog)?;
+                let count = overrides.len();
+                write_override_file(
+                    PathBuf::from(path_str),
+                    &OverrideFile { catalog, overrides },
+                )?;
+                println!("{}", format!("{count} Korrekturen exportiert.").green());
+            }
+            Some(("lade", sub_matches)) => {
+                let path_str = sub_matches.get_one::<String>("PFAD").expect("required");
+                let file = read_override_file(PathBuf::from(path_str))?;
+                for o in &file.overrides {
+                    db.set_override(&file.catalog, o.number, o.part, o.text.trim())?;
+                }
+                println!(
+                    "{}",
+                    format!(
+                        "{} Korrekturen für den Katalog {} geladen.",
+                        file.overrides.len(),
+                        file.catalog
+                    )
+                    .green()
+                );
+            }
+            _ => unimplemented!(),
+        },
+        Some(("frage", sub_matches)) => custom_question_command(&db, sub_matches)?,
         Some(("loesche", sub_matches)) => match (*sub_matches).subcommand() {
             Some(("fragen", _)) => {
                 if !yn_inquire("Wollen Sie die Fragen wirklich aus der Datenbank löschen?")? {
@@ -153,13 +395,78 @@ fn main() -> Result<()> {
                 }
             }
 
+            let scoring = *matches.get_one::<Scoring>("wertung").expect("default");
+            let answer_mode = *matches.get_one::<AnswerMode>("modus").expect("default");
+            let mut selection = Selection {
+                order: *matches.get_one::<Order>("reihenfolge").expect("default"),
+                no_repeat: *matches.get_one::<u32>("sperre").expect("default"),
+                ..Default::default()
+            };
+            if let Some(days) = matches.get_one::<u32>("fehlerkorb") {
+                selection.pool = Pool::Mistakes { days: *days };
+            }
+            if matches.get_flag("lesezeichen") {
+                selection.pool = Pool::Bookmarks;
+            }
+            if matches.contains_id("sammlung") {
+                selection.pool = Pool::Custom {
+                    deck: matches.get_one::<String>("sammlung").cloned(),
+                };
+            }
+            selection.filter = Filter {
+                from: matches.get_one::<usize>("von").copied(),
+                to: matches.get_one::<usize>("bis").copied(),
+                sections: matches
+                    .get_many::<String>("abschnitt")
+                    .unwrap_or_default()
+                    .cloned()
+                    .collect(),
+                progress: if matches.get_flag("nur-neue") {
+                    Progress::New
+                } else if matches.get_flag("nur-gemeistert") {
+                    Progress::Mastered
+                } else {
+                    Progress::Open
+                },
+            };
+
+            let learn_flags_given = LEARN_FLAGS
+                .iter()
+                .any(|id| matches.value_source(id) == Some(ValueSource::CommandLine));
+
             if db.is_empty()? {
                 println!(
                     "{}",
                     "Bitte laden Sie das dazugehörige PDF. Mehr dazu in der Anleitung:".yellow()
                 );
                 commands.print_long_help()?;
-            } else if db.no_open_questions()? {
+            } else if let Some(app) = resume_session(&db, learn_flags_given)? {
+                start_learn_tui(entered_alternative_mode, &db, app)?;
+            } else if db.count_questions(&selection)? > 0 {
+                let app = new_learn_app(&db, selection, scoring, answer_mode)?;
+                start_learn_tui(entered_alternative_mode, &db, app)?;
+            } else if let Pool::Mistakes { days } = selection.pool {
+                println!(
+                    "{}",
+                    format!("Der Fehlerkorb der letzten {days} Tage ist leer.").green()
+                );
+            } else if let Pool::Custom { .. } = selection.pool {
+                println!(
+                    "{}",
+                    format!(
+                        "Keine offenen Fragen in {}. Eigene Fragen fügen Sie mit {} oder im Menü (tab) hinzu.",
+                        selection.pool.name(),
+                        "ubilerntui frage hinzufuegen".yellow()
+                    )
+                    .yellow()
+                );
+            } else if selection.pool == Pool::Bookmarks {
+                println!(
+                    "{}",
+                    "Keine offenen Fragen mit Lesezeichen. Lesezeichen setzen Sie beim Lernen mit (b)."
+                        .yellow()
+                );
+            } else if selection.filter == Filter::default() && db.no_open_questions()? {
                 println!(
                     "{}\nSie können diese nochmal lernen via {}.",
                     "Sie haben bereits alle Fragen gelernt!".green(),
@@ -167,21 +474,170 @@ fn main() -> Result<()> {
                 );
                 commands.print_help()?;
             } else {
-                start_learn_tui(entered_alternative_mode, &db)?;
+                println!("{}", "Keine Fragen passen zur Auswahl.".yellow());
+            }
+        }
+    }
+
+    db.close()?;
+
+    Ok(())
+}
+
+/// Runs the subcommands of `frage`, which manage custom questions.
+fn custom_question_command(db: &DB, matches: &ArgMatches) -> Result<()> {
+    let answers = |matches: &ArgMatches, name: &str| -> Option<Vec<String>> {
+        matches
+            .get_many::<String>(name)
+            .map(|answers| answers.map(|a| a.trim().to_owned()).collect())
+    };
+
+    match matches.subcommand() {
+        Some(("hinzufuegen", sub_matches)) => {
+            let deck = sub_matches.get_one::<String>("sammlung").expect("default");
+            let q = CustomQuestion {
+                question: sub_matches
+                    .get_one::<String>("FRAGE")
+                    .expect("required")
+                    .trim()
+                    .to_owned(),
+                right_answers: answers(sub_matches, "richtig").unwrap_or_default(),
+                wrong_answers: answers(sub_matches, "falsch").unwrap_or_default(),
+            };
+            if let Err(err) = q.validate() {
+                println!("{}", err.yellow());
+                return Ok(());
+            }
+            let id = db.insert_custom_question(deck, &q)?;
+            println!(
+                "{}",
+                format!("Eigene Frage {} in {deck} gespeichert.", custom_number(id)).green()
+            );
+        }
+        Some(("bearbeite", sub_matches)) => {
+            let number = *sub_matches.get_one::<usize>("NUMMER").expect("required");
+            let Some(old) = db
+                .get_question(custom_id(number))?
+                .filter(|q| q.custom_deck.is_some())
+            else {
+                println!("{}", format!("Keine eigene Frage {number}.").yellow());
+                return Ok(());
+            };
+            let mut q = CustomQuestion::from_question_answer(&old);
+            if let Some(question) = sub_matches.get_one::<String>("frage") {
+                q.question = question.trim().to_owned();
+            }
+            if let Some(right_answers) = answers(sub_matches, "richtig") {
+                q.right_answers = right_answers;
+            }
+            if let Some(wrong_answers) = answers(sub_matches, "falsch") {
+                q.wrong_answers = wrong_answers;
+            }
+            if let Err(err) = q.validate() {
+                println!("{}", err.yellow());
+                return Ok(());
+            }
+            db.update_custom_question(old.id, &q)?;
+            println!("{}", format!("Eigene Frage {number} gespeichert.").green());
+        }
+        Some(("loesche", sub_matches)) => {
+            let number = *sub_matches.get_one::<usize>("NUMMER").expect("required");
+            if db.delete_custom_question(custom_id(number))? {
+                println!("{}", format!("Eigene Frage {number} gelöscht.").green());
+            } else {
+                println!("{}", format!("Keine eigene Frage {number}.").yellow());
+            }
+        }
+        Some(("liste", sub_matches)) => {
+            let deck = sub_matches.get_one::<String>("sammlung");
+            let questions = db.get_custom_questions(deck.map(String::as_str))?;
+            if questions.is_empty() {
+                println!("{}", "Keine eigenen Fragen.".yellow());
+            }
+            for q in questions {
+                println!(
+                    "{:>4}  {}  {}",
+                    custom_number(q.id),
+                    q.custom_deck.unwrap_or_default().cyan(),
+                    q.question
+                );
+            }
+        }
+        Some(("exportiere", sub_matches)) => {
+            let path_str = sub_matches.get_one::<String>("PFAD").expect("required");
+            let deck = sub_matches.get_one::<String>("sammlung").expect("default");
+            let questions: Vec<CustomQuestion> = db
+                .get_custom_questions(Some(deck))?
+                .iter()
+                .map(CustomQuestion::from_question_answer)
+                .collect();
+            let count = questions.len();
+            write_deck_file(
+                PathBuf::from(path_str),
+                &DeckFile {
+                    deck: deck.clone(),
+                    questions,
+                },
+            )?;
+            println!(
+                "{}",
+                format!("{count} Fragen der Sammlung {deck} exportiert.").green()
+            );
+        }
+        Some(("lade", sub_matches)) => {
+            let path_str = sub_matches.get_one::<String>("PFAD").expect("required");
+            let file = read_deck_file(PathBuf::from(path_str))?;
+            for q in &file.questions {
+                db.insert_custom_question(&file.deck, q)?;
             }
+            println!(
+                "{}",
+                format!(
+                    "{} Fragen in die Sammlung {} geladen.",
+                    file.questions.len(),
+                    file.deck
+                )
+                .green()
+            );
         }
+        _ => unimplemented!(),
     }
 
     Ok(())
 }
 
+/// Runs [DB::check] for `ubilerntui pruefe`.
+///
+/// Returns `None` after telling the user, if the database is too corrupted to be checked at all.
+fn check_db(db: &DB) -> Result<Option<Vec<Problem>>> {
+    match db.check() {
+        Ok(problems) => Ok(Some(problems)),
+        Err(err) if is_corruption(&err) => {
+            println!(
+                "{}",
+                format!(
+                    "Die Datenbank in {} ist so beschädigt, dass sie nicht repariert werden kann. \
+                    Benennen Sie sie um und laden Sie den Katalog neu (ubilerntui lade).",
+                    get_local_dir(DB_DIR_NAME)?.display()
+                )
+                .red()
+            );
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
 /// Inquire the user if action should be taken via simple y/n question.
 fn yn_inquire(what: &str) -> Result<bool> {
     loop {
         print!("{} {} ", what.yellow(), "Y/n:".yellow());
         io::stdout().flush()?;
         let mut s = "".to_owned();
-        io::stdin().read_line(&mut s)?;
+        if io::stdin().read_line(&mut s)? == 0 {
+            // No more input, e.g. the standard input is a closed pipe.
+            return Ok(false);
+        }
         let s = s.to_lowercase();
         let s = s.trim();
         if s == "y" || s == "j" {
@@ -192,43 +648,112 @@ fn yn_inquire(what: &str) -> Result<bool> {
     }
 }
 
+/// Returns [App] for a new session starting with a question out of `selection`.
+fn new_learn_app(
+    db: &DB,
+    selection: Selection,
+    scoring: Scoring,
+    answer_mode: AnswerMode,
+) -> Result<App> {
+    let first_question = db
+        .next_question(&selection)?
+        .ok_or_eyre("Keine Fragen in der Auswahl.")?;
+    let mut app = App::new(
+        first_question,
+        db.get_total_progress(&selection.filter)?,
+        db.get_total_question_count(&selection.filter)?,
+    );
+    app.question_answer.scramble(&mut app.rng);
+    app.scoring = scoring;
+    app.answer_mode = answer_mode;
+    if let Pool::Mistakes { days } = selection.pool {
+        app.mistake_days = days;
+    }
+    app.selection = selection;
+
+    Ok(app)
+}
+
+/// Offers to resume the saved [Session] and returns its [App], if the user wants to.
+///
+/// A session the user declines or which can not be restored anymore is deleted.
+/// If `learn_flags_given`, a new session with these flags is started instead and the saved one is kept,
+/// as the resumed session would ignore them.
+fn resume_session(db: &DB, learn_flags_given: bool) -> Result<Option<App>> {
+    let Some(session) = db.load_session()? else {
+        return Ok(None);
+    };
+    if learn_flags_given {
+        println!(
+            "{}",
+            format!(
+                "Die letzte Sitzung ({}) wird nicht fortgesetzt, da Optionen zum Lernen angegeben wurden.",
+                session.description()
+            )
+            .yellow()
+        );
+        return Ok(None);
+    }
+    let question = format!("Letzte Sitzung fortsetzen ({})?", session.description());
+    if !yn_inquire(&question)? {
+        db.clear_session()?;
+        return Ok(None);
+    }
+
+    let app = session.into_app(db)?;
+    if app.is_none() {
+        println!(
+            "{}",
+            "Die letzte Sitzung kann nicht fortgesetzt werden, da sich die Fragen geändert haben."
+                .yellow()
+        );
+        db.clear_session()?;
+    }
+    Ok(app)
+}
+
 /// Runs TUI for learning.
 ///
 /// This function does:
 /// 1. Enter alternative and raw terminal modes.
 /// 2. Spawns thread for capture of user input.
 /// 3. Main loop:
-///     1. Updates state of [App].
-///     2. Draws TUI.
-///     3. Lets thread sleep for power savings.
-/// 4. Exits alternative and raw terminal modes.
+///     1. Waits for user input (or a tick, if [App::tick_rate] is set).
+///     2. Updates state of [App].
+///     3. Draws TUI.
+///     4. Lets thread sleep if the fps limit would be exceeded.
+/// 4. Stops input thread and exits alternative and raw terminal modes.
+/// 5. Prints the summary of the session.
+/// 6. Saves the [Session] to resume it on the next start, unless every question was learned.
+///
+/// Termination signals (e.g. SIGTERM or SIGHUP) end the main loop like the user would, so the terminal is restored.
+///
+/// The TUI is only redrawn if something happened, thus an idle session does not use any cpu.
 /// ```
 /// let entered_alternative_mode = Arc::new(AtomicBool::new(false));
 /// eyre_term_exit_hook(entered_alternative_mode.clone())?;
 /// let db = DB::new("db")?;
-/// start_learn_tui(entered_alternative_mode, &db)?;
+/// let app = new_learn_app(&db, Selection::default(), Scoring::AllOrNothing, AnswerMode::Choice)?;
+/// start_learn_tui(entered_alternative_mode, &db, app)?;
 /// ```
-fn start_learn_tui(entered_alternative_mode: Arc<AtomicBool>, db: &DB) -> Result<()> {
-    let first_question = db.get_random()?;
-    let mut app = App::new(
-        first_question,
-        db.get_total_progress()?,
-        db.get_total_question_count()?,
-    );
-    app.question_answer.scramble(&mut app.rng);
+fn start_learn_tui(entered_alternative_mode: Arc<AtomicBool>, db: &DB, mut app: App) -> Result<()> {
+    app.catalog_name = db.catalog_info()?.map(|info| info.short_name());
 
     entered_alternative_mode.swap(true, Ordering::Relaxed);
     let mut term = Tui::new_with_term()?;
     term.enter()?;
     trace!("Entered alternative screen mode.");
 
-    let event_handler = event::InputEventHandler::new(FPS);
+    let mut event_handler = event::InputEventHandler::new()?;
 
     let main_span = trace_span!("Main Loop").entered();
 
     let mut fps_timer = fpslimiter::FpsTimer::new(FPS);
+    term.draw(&mut app)?;
     loop {
-        while let Ok(event) = event_handler.receiver.try_recv() {
+        let event = event_handler.next(app.tick_rate)?;
+        update::update(event, &mut app, db)?;
+        while let Some(event) = event_handler.try_next()? {
             update::update(event, &mut app, db)?;
         }
         if app.exit {
@@ -240,7 +765,19 @@ fn start_learn_tui(entered_alternative_mode: Arc<AtomicBool>, db: &DB) -> Result
     }
     main_span.exit();
 
+    event_handler.stop();
     term.exit()?;
 
+    if let Some(message) = app.finished_message() {
+        println!("{}", message.green());
+    }
+    println!("{}", app.stats.compact());
+
+    if app.finished {
+        db.clear_session()?;
+    } else {
+        db.save_session(&Session::from_app(&app))?;
+    }
+
     Ok(())
 }
@@ -0,0 +1,77 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use crate::app::App;
+use crate::selection::{Order, Pool};
+
+/// Single line of the menu, which is opened with tab.
+pub struct MenuEntry {
+    pub label: String,
+    pub action: MenuAction,
+}
+
+/// What happens if the user chooses a [MenuEntry].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MenuAction {
+    /// Train only the questions of this pool.
+    Pool(Pool),
+    /// Ask the questions in this order.
+    Order(Order),
+    /// Open the form for a new custom question.
+    NewQuestion,
+}
+
+/// Returns the entries of the menu. The currently active choices are marked.
+pub fn entries(app: &App) -> Vec<MenuEntry> {
+    let pools = [
+        Pool::All,
+        Pool::Mistakes {
+            days: app.mistake_days,
+        },
+        Pool::Bookmarks,
+        Pool::Custom { deck: None },
+    ];
+
+    let pool_entries = pools.into_iter().map(|pool| MenuEntry {
+        label: with_marker(pool.name(), app.selection.pool == pool),
+        action: MenuAction::Pool(pool),
+    });
+    let order_entries = Order::ALL.into_iter().map(|order| MenuEntry {
+        label: with_marker(
+            format!("Reihenfolge: {}", order.name()),
+            app.selection.order == order,
+        ),
+        action: MenuAction::Order(order),
+    });
+
+    let new_question_entry = MenuEntry {
+        label: "    Neue eigene Frage…".to_owned(),
+        action: MenuAction::NewQuestion,
+    };
+
+    pool_entries
+        .chain(order_entries)
+        .chain([new_question_entry])
+        .collect()
+}
+
+fn with_marker(label: String, active: bool) -> String {
+    if active {
+        format!("(•) {label}")
+    } else {
+        format!("( ) {label}")
+    }
+}
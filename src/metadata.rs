@@ -0,0 +1,163 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::fmt;
+
+use chrono::Local;
+use sha2::{Digest, Sha256};
+
+use crate::pdfparser::{Catalog, Extraction};
+
+/// Where the loaded catalog came from, saved in the table `meta` on import.
+///
+/// Databases of older versions only know the `catalog`, everything else is `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CatalogInfo {
+    /// Key of the [CatalogFormat](crate::pdfparser::CatalogFormat).
+    pub catalog: String,
+    pub title: Option<String>,
+    /// Edition as printed in the catalog, e.g. `01.07.2023`.
+    pub edition: Option<String>,
+    /// File name of the catalog.
+    pub source: Option<String>,
+    /// SHA-256 of the loaded file as hex.
+    pub sha256: Option<String>,
+    /// Key of the [Extraction] used.
+    pub extraction: Option<String>,
+    /// Local time of the import, e.g. `2026-10-19 14:02:11`.
+    pub imported_at: Option<String>,
+    /// Version of ubilerntui, which imported the catalog.
+    pub app_version: Option<String>,
+}
+
+impl CatalogInfo {
+    /// Count of the fields saved in the table `meta`.
+    pub const FIELD_COUNT: usize = 8;
+
+    /// Keys of the fields in the table `meta`, in order of [CatalogInfo::values].
+    pub const KEYS: [&'static str; Self::FIELD_COUNT] = [
+        "catalog",
+        "catalog_title",
+        "catalog_edition",
+        "catalog_source",
+        "catalog_sha256",
+        "catalog_extraction",
+        "catalog_imported_at",
+        "catalog_app_version",
+    ];
+
+    /// Describes `catalog` parsed via `extraction` out of the file `source` with the content `bytes`, imported now.
+    pub fn new(catalog: &Catalog, source: &str, bytes: &[u8], extraction: Extraction) -> Self {
+        CatalogInfo {
+            catalog: catalog.format.key().to_owned(),
+            title: catalog.title.clone(),
+            edition: catalog.edition.clone(),
+            source: Some(source.to_owned()),
+            sha256: Some(format!("{:x}", Sha256::digest(bytes))),
+            extraction: Some(extraction.key().to_owned()),
+            imported_at: Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+            app_version: Some(env!("CARGO_PKG_VERSION").to_owned()),
+        }
+    }
+
+    /// Returns the values of the fields, in order of [CatalogInfo::KEYS].
+    pub fn values(&self) -> [Option<&str>; Self::FIELD_COUNT] {
+        [
+            Some(self.catalog.as_str()),
+            self.title.as_deref(),
+            self.edition.as_deref(),
+            self.source.as_deref(),
+            self.sha256.as_deref(),
+            self.extraction.as_deref(),
+            self.imported_at.as_deref(),
+            self.app_version.as_deref(),
+        ]
+    }
+
+    /// Builds the info out of the values of [CatalogInfo::KEYS], if there is a `catalog`.
+    pub fn from_values(values: [Option<String>; Self::FIELD_COUNT]) -> Option<Self> {
+        let [catalog, title, edition, source, sha256, extraction, imported_at, app_version] =
+            values;
+        Some(CatalogInfo {
+            catalog: catalog?,
+            title,
+            edition,
+            source,
+            sha256,
+            extraction,
+            imported_at,
+            app_version,
+        })
+    }
+
+    /// Short name for the title of the TUI, e.g. `UBI · Stand 01.07.2023`.
+    pub fn short_name(&self) -> String {
+        match &self.edition {
+            Some(edition) => format!("{} · Stand {edition}", self.catalog),
+            None => self.catalog.clone(),
+        }
+    }
+}
+
+impl fmt::Display for CatalogInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unknown = "unbekannt";
+        let lines = [
+            ("Katalog", Some(self.catalog.as_str())),
+            ("Titel", self.title.as_deref()),
+            ("Stand", self.edition.as_deref()),
+            ("Datei", self.source.as_deref()),
+            ("SHA-256", self.sha256.as_deref()),
+            ("Extraktion", self.extraction.as_deref()),
+            ("Geladen am", self.imported_at.as_deref()),
+            ("Geladen mit Version", self.app_version.as_deref()),
+        ];
+        for (name, value) in lines {
+            writeln!(f, "{:<20} {}", format!("{name}:"), value.unwrap_or(unknown))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pdfparser::CatalogFormat;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_catalog_info() {
+        let catalog = Catalog {
+            format: CatalogFormat::Src,
+            questions: vec![],
+            issues: vec![],
+            title: None,
+            edition: Some("01.07.2023".to_owned()),
+        };
+        let info = CatalogInfo::new(&catalog, "src.pdf", b"abc", Extraction::Layout);
+
+        assert_eq!(
+            info.sha256.as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(info.extraction.as_deref(), Some("layout"));
+        assert_eq!(info.short_name(), "SRC · Stand 01.07.2023");
+
+        let values = info.values().map(|value| value.map(str::to_owned));
+        assert_eq!(CatalogInfo::from_values(values), Some(info));
+        assert_eq!(CatalogInfo::from_values(Default::default()), None);
+    }
+}
@@ -0,0 +1,98 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+/// File with corrections of the imported texts of one catalog, e.g. to share them with others.
+///
+/// Corrections are matched with questions by the key of the catalog (e.g. `UBI`) and the question number.
+/// `teil` 0 is the question, `teil` `n` the `n`-th answer in order of the catalog.
+///
+/// ```json
+/// {
+///   "katalog": "UBI",
+///   "korrekturen": [
+///     { "frage": 12, "teil": 0, "text": "Was bedeutet das Schallsignal \"ein langer Ton\"?" },
+///     { "frage": 12, "teil": 2, "text": "Achtung" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OverrideFile {
+    #[serde(rename = "katalog")]
+    pub catalog: String,
+    #[serde(rename = "korrekturen")]
+    pub overrides: Vec<Override>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Override {
+    /// Number of the question in the catalog.
+    #[serde(rename = "frage")]
+    pub number: usize,
+    /// 0 for the question, `n` for the `n`-th answer in order of the catalog.
+    #[serde(rename = "teil")]
+    pub part: usize,
+    pub text: String,
+}
+
+/// Reads an [OverrideFile] in json format from `path`.
+pub fn read_override_file(path: PathBuf) -> Result<OverrideFile> {
+    let s = read_to_string(&path)?;
+    parse_override_file(&s).wrap_err_with(|| format!("Ungültige Korrekturdatei {}", path.display()))
+}
+
+/// Writes `file` in json format to `path`.
+pub fn write_override_file(path: PathBuf, file: &OverrideFile) -> Result<()> {
+    write(path, serde_json::to_string_pretty(file)? + "\n")?;
+    Ok(())
+}
+
+fn parse_override_file(s: &str) -> Result<OverrideFile> {
+    Ok(serde_json::from_str(s)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_override_file_roundtrip() -> Result<()> {
+        let file = OverrideFile {
+            catalog: "UBI".to_owned(),
+            overrides: vec![Override {
+                number: 12,
+                part: 2,
+                text: "Achtung".to_owned(),
+            }],
+        };
+
+        let s = serde_json::to_string(&file)?;
+        assert!(s.contains(r#""teil":2"#));
+        assert_eq!(parse_override_file(&s)?, file);
+        assert!(
+            parse_override_file(r#"{ "katalog": "UBI", "korrekturen": [{ "frage": 1 }] }"#)
+                .is_err()
+        );
+
+        Ok(())
+    }
+}
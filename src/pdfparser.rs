@@ -14,76 +14,372 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
+use std::collections::HashSet;
 use std::fs::read;
+use std::io::{stdin, Read};
 use std::path::PathBuf;
 
 use color_eyre::eyre::Result;
 use fancy_regex::Regex;
 use once_cell::sync::Lazy;
-use pdf_extract::extract_text_from_mem;
+use pdf_extract::extract_text_from_mem_by_pages;
 
-/// Uses [pdf_extract] crate to [extract](pdf_extract::extract_text_from_mem) PDF read on location of `path`.
-pub fn read_pdf_to_string(path: PathBuf) -> Result<String> {
+use crate::app::QuestionAnswer;
+use crate::cleantext::{clean_text, normalize};
+use crate::issues::{page_of, IssueKind, ParseIssue};
+
+/// Uses [pdf_extract] crate to [extract](pdf_extract::extract_text_from_mem_by_pages) PDF read on location of `path`.
+///
+/// Returns the text of every page. Each page starts with line breaks, so joined they read like the whole PDF.
+pub fn read_pdf_pages(path: PathBuf) -> Result<Vec<String>> {
     let bytes = read(path)?;
-    Ok(extract_text_from_mem(&bytes)?)
+    Ok(extract_text_from_mem_by_pages(&bytes)?)
+}
+
+/// Separator of the pages in text files, as written by `pdftotext` and `ubilerntui text extrahieren`.
+pub const PAGE_SEPARATOR: char = '\x0c';
+
+/// Source of the text of a catalog, given as path on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextSource {
+    /// `-` reads the text from the standard input.
+    Stdin,
+    /// Already extracted text (e.g. by `ubilerntui text extrahieren` or other tools) in files ending with `.txt`.
+    Text(PathBuf),
+    Pdf(PathBuf),
+}
+
+impl TextSource {
+    pub fn from_arg(arg: &str) -> Self {
+        let path = PathBuf::from(arg);
+        if arg == "-" {
+            TextSource::Stdin
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("txt"))
+        {
+            TextSource::Text(path)
+        } else {
+            TextSource::Pdf(path)
+        }
+    }
+
+    /// Returns the content of the source.
+    pub fn read(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        match self {
+            TextSource::Stdin => {
+                stdin().read_to_end(&mut bytes)?;
+            }
+            TextSource::Text(path) | TextSource::Pdf(path) => bytes = read(path)?,
+        }
+        Ok(bytes)
+    }
+
+    /// Name of the source for the user, e.g. the file name.
+    pub fn name(&self) -> String {
+        match self {
+            TextSource::Stdin => "Standardeingabe".to_owned(),
+            TextSource::Text(path) | TextSource::Pdf(path) => path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+        }
+    }
+
+    /// Returns the text of every page of the `bytes` [read](TextSource::read) from this source.
+    ///
+    /// Text is split into pages at [PAGE_SEPARATOR].
+    pub fn pages(&self, bytes: &[u8]) -> Result<Vec<String>> {
+        Ok(match self {
+            TextSource::Stdin | TextSource::Text(_) => split_pages(std::str::from_utf8(bytes)?),
+            TextSource::Pdf(_) => extract_text_from_mem_by_pages(bytes)?,
+        })
+    }
+}
+
+/// Splits `s` into pages at [PAGE_SEPARATOR].
+fn split_pages(s: &str) -> Vec<String> {
+    s.split(PAGE_SEPARATOR).map(str::to_owned).collect()
 }
 
-/// Trims, replaces bad line breaks and multiple spaces within string.
-macro_rules! to_trimmed_string {
-    ($e:expr) => {{
-        let s = ($e).trim();
-        let s = s.replace("-\n", "");
-        let s = s.replace("\n", " ");
-        let s = s.replace("   ", " ");
-        let s = s.replace("  ", " ");
-        String::from(s)
-    }};
+/// Applies `reg` on `s` and builds a [QuestionAnswer] per match.
+///
+/// `reg` needs the named groups `id`, `question` and `answers`, an optional group `id2` is compared with `id`.
+/// The group `answers` holds all answers, which are split by `label` (e.g. `b)`).
+/// The first answer is the right one.
+/// Every question gets the last section header in front of it (see [extract_sections])
+/// and the number of the page it starts on, if the byte positions of the pages in `s` are given by `page_starts`.
+///
+/// If `reg` fails, the error is saved as [ParseIssue] and the search continues at the next line.
+/// Questions without a valid or with an already used number are skipped and saved as issue as well.
+fn extract_questions(
+    reg: &Regex,
+    label: &Regex,
+    s: &str,
+    page_starts: &[usize],
+) -> (Vec<QuestionAnswer>, Vec<ParseIssue>) {
+    let sections = extract_sections(s);
+    let mut questions = vec![];
+    let mut issues = vec![];
+    let mut numbers = HashSet::new();
+    let mut pos = 0;
+    let mut failed = false;
+
+    while pos < s.len() {
+        let caps = match reg.captures_from_pos(s, pos) {
+            Ok(Some(caps)) => caps,
+            Ok(None) => break,
+            Err(err) => {
+                // Consecutive failures are reported once.
+                if !failed {
+                    let end = s[pos..].find("\n\n").map_or(s.len(), |i| pos + i);
+                    issues.push(
+                        ParseIssue::new(IssueKind::Regex(err.to_string()), s, pos, end)
+                            .with_page(page_starts),
+                    );
+                }
+                failed = true;
+                pos = s[pos..].find('\n').map_or(s.len(), |i| pos + i + 1);
+                continue;
+            }
+        };
+        failed = false;
+        let whole = caps.get(0).expect("whole match");
+        let (start, end) = (whole.start(), whole.end());
+        pos = end.max(pos + 1);
+        let issue = |kind| ParseIssue::new(kind, s, start, end).with_page(page_starts);
+
+        let Ok(id) = caps["id"].parse::<usize>() else {
+            issues.push(issue(IssueKind::InvalidNumber));
+            continue;
+        };
+        if !numbers.insert(id) {
+            issues.push(issue(IssueKind::DuplicateNumber).with_number(id));
+            continue;
+        }
+        if let Some(id2) = caps.name("id2").and_then(|id2| id2.as_str().parse().ok()) {
+            if id2 != id {
+                issues.push(issue(IssueKind::NumberMismatch(id2)).with_number(id));
+            }
+        }
+
+        let answers: Vec<String> = label
+            .split(&caps["answers"])
+            .filter_map(|answer| answer.ok())
+            .skip(1)
+            .map(clean_text)
+            .collect();
+        let mut q = QuestionAnswer::new(id, clean_text(&caps["question"]), answers, vec![0]);
+        if q.question.is_empty() || q.possible_answers.iter().any(String::is_empty) {
+            issues.push(issue(IssueKind::EmptyText).with_number(id));
+        }
+        q.section = sections
+            .iter()
+            .take_while(|(position, _)| *position < start)
+            .last()
+            .map(|(_, section)| section.clone());
+        q.page = page_of(page_starts, start);
+        questions.push(q);
+    }
+
+    (questions, issues)
+}
+
+/// Returns an [IssueKind::Unmatched] issue for every line of `s` starting with the number of a question (e.g. `12.`),
+/// if no question with that number was parsed.
+///
+/// This skips other numbered lines of the catalog, e.g. in its preface.
+fn unmatched_questions(s: &str, page_starts: &[usize], catalog: &Catalog) -> Vec<ParseIssue> {
+    static REG_START: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?m)^[ \t]*(?P<id>[0-9]{1,3})\.(?:[ \t]|$)").unwrap());
+
+    let numbers: HashSet<usize> = catalog.questions.iter().map(|q| q.id).collect();
+    let starts: Vec<(usize, usize)> = REG_START
+        .captures_iter(s)
+        .filter_map(|caps| caps.ok())
+        .filter_map(|caps| Some((caps.get(0)?.start(), caps["id"].parse().ok()?)))
+        .collect();
+
+    starts
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, id))| !numbers.contains(id))
+        .map(|(i, &(start, id))| {
+            let end = starts.get(i + 1).map_or(s.len(), |&(next, _)| next);
+            ParseIssue::new(IssueKind::Unmatched, s, start, end)
+                .with_number(id)
+                .with_page(page_starts)
+        })
+        .collect()
 }
 
-fn extract_questions(reg: &Regex, s: &str) -> Vec<(usize, String, String, Vec<String>)> {
-    reg.captures_iter(s)
+/// Returns the section headers of the catalog with their byte position in `s`.
+///
+/// A header is a roman numeral followed by a dot and its title on the same or the next line,
+/// e.g. `II. Betriebsverfahren`.
+fn extract_sections(s: &str) -> Vec<(usize, String)> {
+    static REG_SECTION: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?m)^[ \t]*(?P<numeral>(?=[IVX])X{0,3}(?:IX|IV|V?I{0,3}))\.\s*?(?P<title>[^\s0-9\[][^\n]*?)[ \t]*$")
+            .unwrap()
+    });
+
+    REG_SECTION
+        .captures_iter(s)
         .filter_map(|caps| caps.ok())
         .map(|caps| {
-            let id: usize = caps["id"].parse().unwrap();
-            (
-                id,
-                to_trimmed_string!(&caps["question"]),
-                to_trimmed_string!(&caps["a"]),
-                vec![
-                    to_trimmed_string!(&caps["b"]),
-                    to_trimmed_string!(&caps["c"]),
-                    to_trimmed_string!(&caps["d"]),
-                ],
-            )
+            let position = caps.get(0).map_or(0, |m| m.start());
+            let section = format!("{}. {}", &caps["numeral"], clean_text(&caps["title"]));
+            (position, section)
         })
         .collect()
 }
 
+/// Format of a question catalog, which also identifies the catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    /// UBI/Binnenschifffahrt, answers labeled a), b), c), ...
+    Ubi,
+    /// SRC/UKW-See, answers labeled 1), 2), 3), ...
+    Src,
+}
+
+impl CatalogFormat {
+    /// Key of the catalog, e.g. used to match explanations with questions.
+    pub fn key(&self) -> &'static str {
+        match self {
+            CatalogFormat::Ubi => "UBI",
+            CatalogFormat::Src => "SRC",
+        }
+    }
+}
+
+/// Backend used to extract the questions out of a catalog PDF.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extraction {
+    /// Parses the plain text of the PDF, see [parse_pdf_pages].
+    #[default]
+    Text,
+    /// Parses the positioned lines of the PDF, see [parse_layout](crate::layout::parse_layout).
+    Layout,
+}
+
+impl Extraction {
+    /// Key of the extraction, as given on the command line.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Extraction::Text => "text",
+            Extraction::Layout => "layout",
+        }
+    }
+}
+
+/// Questions parsed out of a catalog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Catalog {
+    pub format: CatalogFormat,
+    pub questions: Vec<QuestionAnswer>,
+    /// Problems of questions, which could not or only partly be parsed.
+    pub issues: Vec<ParseIssue>,
+    /// Title of the catalog, see [extract_title].
+    pub title: Option<String>,
+    /// Edition of the catalog, see [extract_edition].
+    pub edition: Option<String>,
+}
+
+/// Returns the first paragraph of `s` mentioning a `Fragenkatalog` as title of the catalog.
+pub fn extract_title(s: &str) -> Option<String> {
+    static REG_PARAGRAPH_BREAK: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n[ \t]*\n").unwrap());
+
+    REG_PARAGRAPH_BREAK
+        .split(s)
+        .filter_map(|paragraph| paragraph.ok())
+        .find(|paragraph| paragraph.to_lowercase().contains("fragenkatalog"))
+        .map(clean_text)
+}
+
+/// Returns the date after the first `Stand` or `Ausgabe` in `s` as edition of the catalog,
+/// e.g. `01.07.2023` or `Juli 2023`.
+pub fn extract_edition(s: &str) -> Option<String> {
+    static REG_EDITION: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"(?i)\b(?:Stand|Ausgabe)\b[: \t]*(?:vom[ \t]+)?(?P<edition>[0-9]{1,2}\.[ \t]?[0-9]{1,2}\.[ \t]?[0-9]{2,4}|(?:[0-9]{1,2}\.[ \t]*)?\p{L}+[ \t]+[0-9]{4}|[0-9]{4})").unwrap()
+    });
+
+    let caps = REG_EDITION.captures(s).ok().flatten()?;
+    Some(clean_text(&caps["edition"]))
+}
+
 /// Uses regex to parse out all questions from string.
 /// Supports two formats:
-/// - UBI/Binnenschifffahrt: answers labeled a), b), c), d) with inline [id] bracket
-/// - SRC/UKW-See: answers labeled 1), 2), 3), 4)
-pub fn parse_pdf(s: String) -> Result<Vec<(usize, String, String, Vec<String>)>> {
-    // UBI format: a), b), c), d) with [id] bracket inline in question
+/// - UBI/Binnenschifffahrt: answers labeled a), b), c), ... with inline [id] bracket
+/// - SRC/UKW-See: answers labeled 1), 2), 3), ...
+///
+/// Questions need at least two answers.
+/// Questions, which could not be parsed, are saved in `issues` (see [extract_questions] and [unmatched_questions]).
+/// The first answer is the right one, so `right_answers` is always `[0]`.
+/// Section headers with roman numerals (e.g. `II. Betriebsverfahren`) are saved in `section`.
+/// The text is [normalized](normalize) first and every field is [cleaned](clean_text).
+///
+/// The questions have no page, see [parse_pdf_pages].
+pub fn parse_pdf(s: String) -> Result<Catalog> {
+    parse_text(&normalize(&s), &[])
+}
+
+/// Parses the questions of the text of every page of a PDF like [parse_pdf]
+/// and saves the number of the page each question starts on in `page`.
+///
+/// A page not ending with a line break is followed by one, so its last line is not joined with the first of the next page.
+pub fn parse_pdf_pages(pages: &[String]) -> Result<Catalog> {
+    let mut s = String::new();
+    let mut page_starts = Vec::with_capacity(pages.len());
+    for page in pages {
+        if !s.is_empty() && !s.ends_with('\n') {
+            s.push('\n');
+        }
+        page_starts.push(s.len());
+        s.push_str(&normalize(page));
+    }
+    parse_text(&s, &page_starts)
+}
+
+/// Parses the normalized text `s` of a catalog, see [extract_questions] for `page_starts`.
+fn parse_text(s: &str, page_starts: &[usize]) -> Result<Catalog> {
+    // UBI format: a), b), c), ... with [id] bracket inline in question.
+    // Answers are separated by blank lines and end at the first blank line after the last label.
     static REG_UBI: Lazy<Regex> = Lazy::new(|| {
         Regex::new(
-            r"(?ms)^\s?(?P<id>[0-9]{1,3})\.\s+(?P<question>.*?)\s+\[(?P<id2>[0-9]{1,3})\].*?a\)(?P<a>.*?)b\)(?P<b>.*?)c\)(?P<c>.*?)d\)(?P<d>.*?)\n$"
+            r"(?ms)^\s?(?P<id>[0-9]{1,3})\.\s+(?P<question>.*?)\s+\[(?P<id2>[0-9]{1,3})\].*?(?P<answers>a\)(?:(?!\n\s*\n).)*?(?:\s*\n\s*[b-z]\)(?:(?!\n\s*\n).)*?)+)(?=\n\s*\n|\z)"
       ).unwrap()
     });
+    static LABEL_UBI: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:\A|\s*\n\s*)[a-z]\)").unwrap());
 
-    // SRC format: 1), 2), 3), 4) — [id] bracket position is inconsistent
+    // SRC format: 1), 2), 3), ... — [id] bracket position is inconsistent
     static REG_SRC: Lazy<Regex> = Lazy::new(|| {
         Regex::new(
-            r"(?ms)(?:(?<=\n\s)|(?<=\n))(?P<id>[0-9]{1,3})\.\s+(?P<question>.+?)\[[0-9]{1,3}\]\s*?\n1\)(?P<a>.+?)\n2\)(?P<b>.+?)\n3\)(?P<c>.+?)\n4\)(?P<d>.+?)(?=Gesamtfragenkatalog|[0-9]{1,3}\.|(?:IX|IV|V?I{1,3})\.|\z)"
+            r"(?ms)(?:(?<=\n\s)|(?<=\n))(?P<id>[0-9]{1,3})\.\s+(?P<question>.+?)\[[0-9]{1,3}\]\s*?\n(?P<answers>1\).+?(?:\n[0-9]{1,2}\).+?)+)(?=Gesamtfragenkatalog|^\s*(?:[0-9]{1,3}|IX|IV|V?I{1,3})\.|\z)"
       ).unwrap()
     });
+    static LABEL_SRC: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:\A|\n)[0-9]{1,2}\)").unwrap());
 
-    let ubi_results = extract_questions(&REG_UBI, &s);
-    if !ubi_results.is_empty() {
-        return Ok(ubi_results);
+    let (mut questions, mut issues) = extract_questions(&REG_UBI, &LABEL_UBI, s, page_starts);
+    let mut format = CatalogFormat::Ubi;
+    if questions.is_empty() {
+        (questions, issues) = extract_questions(&REG_SRC, &LABEL_SRC, s, page_starts);
+        format = CatalogFormat::Src;
     }
 
-    Ok(extract_questions(&REG_SRC, &s))
+    let mut catalog = Catalog {
+        format,
+        questions,
+        issues,
+        title: extract_title(s),
+        edition: extract_edition(s),
+    };
+    let mut unmatched = unmatched_questions(s, page_starts, &catalog);
+    catalog.issues.append(&mut unmatched);
+    catalog.issues.sort_by_key(|issue| issue.offset);
+    Ok(catalog)
 }
 
 #[cfg(test)]
@@ -151,39 +447,44 @@ wrong answer 3 part 2
             ";
 
         let expected = vec![
-            (
-                128usize,
-                "q part1 q part2".to_owned(),
-                "correct answer".to_owned(),
+            QuestionAnswer::new(
+                128,
+                "q part1 q part2",
                 vec![
-                    "wrong answer 1".to_owned(),
-                    "wrong answer 2".to_owned(),
-                    "wrong answer 3".to_owned(),
+                    "correct answer",
+                    "wrong answer 1",
+                    "wrong answer 2",
+                    "wrong answer 3",
                 ],
+                vec![0],
             ),
-            (
+            QuestionAnswer::new(
                 129,
-                "q part1 q part2".to_owned(),
-                "correct answer".to_owned(),
+                "q part1 q part2",
                 vec![
-                    "wrong answer 1".to_owned(),
-                    "wrong answer 2".to_owned(),
-                    "wrong answer 3".to_owned(),
+                    "correct answer",
+                    "wrong answer 1",
+                    "wrong answer 2",
+                    "wrong answer 3",
                 ],
+                vec![0],
             ),
-            (
+            QuestionAnswer::new(
                 127,
-                "q part1 q part2".to_owned(),
-                "correct answer part 1 correct answer part 2".to_owned(),
+                "q part1 q part2",
                 vec![
-                    "wrong answer 1 part 1 wrong answer 1 part 2 wrong answer 1 part 3".to_owned(),
-                    "wrong answer 2 part 1 wrong answer 2 part 2".to_owned(),
-                    "wrong answer 3 part 1 wrong answer 3 part 2".to_owned(),
+                    "correct answer part 1 correct answer part 2",
+                    "wrong answer 1 part 1 wrong answer 1 part 2 wrong answer 1 part 3",
+                    "wrong answer 2 part 1 wrong answer 2 part 2",
+                    "wrong answer 3 part 1 wrong answer 3 part 2",
                 ],
+                vec![0],
             ),
         ];
 
         let res = parse_pdf(test_raw_string.to_owned())?;
+        assert_eq!(res.format, CatalogFormat::Ubi);
+        let res = res.questions;
 
         assert_eq!(res, expected);
 
@@ -217,18 +518,19 @@ q part3
 
         ";
 
-        let expected = vec![(
-            176usize,
-            "q part1 q part2 q part3".to_owned(),
-            "correct answer".to_owned(),
+        let expected = vec![QuestionAnswer::new(
+            176,
+            "q part1 q part2 q part3",
             vec![
-                "wrong answer 1".to_owned(),
-                "wrong answer 2".to_owned(),
-                "wrong answer 3".to_owned(),
+                "correct answer",
+                "wrong answer 1",
+                "wrong answer 2",
+                "wrong answer 3",
             ],
+            vec![0],
         )];
 
-        let res = parse_pdf(raw_string.to_owned())?;
+        let res = parse_pdf(raw_string.to_owned())?.questions;
 
         assert_eq!(res, expected);
 
@@ -299,25 +601,21 @@ Gesamtfragenkatalog
         ";
 
         let expected = vec![
-            (
-                177usize,
-                "q part1 q part2 q part3".to_owned(),
-                "correct answer part1 correct answer part2".to_owned(),
+            QuestionAnswer::new(
+                177,
+                "q part1 q part2 q part3",
                 vec![
-                    "wrong answer 1 part 1 wrong answer 1 part 2".to_owned(),
-                    "wrong answer 2 part 1 wrong answer 2 part 2".to_owned(),
-                    "wrong answer 3 part 1 wrong answer 3 part 2".to_owned(),
+                    "correct answer part1 correct answer part2",
+                    "wrong answer 1 part 1 wrong answer 1 part 2",
+                    "wrong answer 2 part 1 wrong answer 2 part 2",
+                    "wrong answer 3 part 1 wrong answer 3 part 2",
                 ],
+                vec![0],
             ),
-            (
-                178usize,
-                "q q".to_owned(),
-                "a".to_owned(),
-                vec!["a".to_owned(), "a".to_owned(), "a".to_owned()],
-            ),
+            QuestionAnswer::new(178, "q q", vec!["a", "a", "a", "a"], vec![0]),
         ];
 
-        let res = parse_pdf(raw_string.to_owned())?;
+        let res = parse_pdf(raw_string.to_owned())?.questions;
 
         assert_eq!(res, expected);
 
@@ -368,18 +666,19 @@ wrong answer 3 part 2
 Gesamtfragenkatalog
         ";
 
-        let expected = vec![(
-            177usize,
-            "q part1 q part2 q part3".to_owned(),
-            "correct answer part1 correct answer part2".to_owned(),
+        let expected = vec![QuestionAnswer::new(
+            177,
+            "q part1 q part2 q part3",
             vec![
-                "wrong answer 1 part 1 wrong answer 1 part 2".to_owned(),
-                "wrong answer 2 part 1 wrong answer 2 part 2".to_owned(),
-                "wrong answer 3 part 1 wrong answer 3 part 2".to_owned(),
+                "correct answer part1 correct answer part2",
+                "wrong answer 1 part 1 wrong answer 1 part 2",
+                "wrong answer 2 part 1 wrong answer 2 part 2",
+                "wrong answer 3 part 1 wrong answer 3 part 2",
             ],
+            vec![0],
         )];
 
-        let res = parse_pdf(raw_string.to_owned())?;
+        let res = parse_pdf(raw_string.to_owned())?.questions;
 
         assert_eq!(res, expected);
 
@@ -430,18 +729,19 @@ wrong answer 3 part 2
 IV.
         ";
 
-        let expected = vec![(
-            177usize,
-            "q part1 q part2 q part3".to_owned(),
-            "correct answer part1 correct answer part2".to_owned(),
+        let expected = vec![QuestionAnswer::new(
+            177,
+            "q part1 q part2 q part3",
             vec![
-                "wrong answer 1 part 1 wrong answer 1 part 2".to_owned(),
-                "wrong answer 2 part 1 wrong answer 2 part 2".to_owned(),
-                "wrong answer 3 part 1 wrong answer 3 part 2".to_owned(),
+                "correct answer part1 correct answer part2",
+                "wrong answer 1 part 1 wrong answer 1 part 2",
+                "wrong answer 2 part 1 wrong answer 2 part 2",
+                "wrong answer 3 part 1 wrong answer 3 part 2",
             ],
+            vec![0],
         )];
 
-        let res = parse_pdf(raw_string.to_owned())?;
+        let res = parse_pdf(raw_string.to_owned())?.questions;
 
         assert_eq!(res, expected);
 
@@ -468,18 +768,19 @@ VII.
 
         ";
 
-        let expected = vec![(
-            124usize,
-            "question".to_owned(),
-            "correct answer".to_owned(),
+        let expected = vec![QuestionAnswer::new(
+            124,
+            "question",
             vec![
-                "wrong answer 1".to_owned(),
-                "wrong answer 2 (A1 bis A4)".to_owned(),
-                "wrong answer 3".to_owned(),
+                "correct answer",
+                "wrong answer 1",
+                "wrong answer 2 (A1 bis A4)",
+                "wrong answer 3",
             ],
+            vec![0],
         )];
 
-        let res = parse_pdf(raw_string.to_owned())?;
+        let res = parse_pdf(raw_string.to_owned())?.questions;
 
         assert_eq!(res, expected);
 
@@ -536,21 +837,280 @@ c. answer
  158.
         ";
 
-        let expected = vec![(
+        let expected = vec![QuestionAnswer::new(
             157,
-            "q q".to_owned(),
-            "a. answer b. answer c. answer".to_owned(),
+            "q q",
             vec![
-                "a. answer b. answer c. answer".to_owned(),
-                "a. answer b. answer c. answer".to_owned(),
-                "a. answer b. answer c. answer".to_owned(),
+                "a. answer b. answer c. answer",
+                "a. answer b. answer c. answer",
+                "a. answer b. answer c. answer",
+                "a. answer b. answer c. answer",
             ],
+            vec![0],
         )];
 
-        let res = parse_pdf(raw_string.to_owned())?;
+        let res = parse_pdf(raw_string.to_owned())?.questions;
 
         assert_eq!(res, expected);
 
         Ok(())
     }
+
+    #[test]
+    fn test_variable_answer_count() -> Result<()> {
+        let raw_string = "
+
+1.   true or false   [1]
+
+a)   true
+
+b)   false
+
+
+
+ 2.   q part1
+q part2   [2]
+
+a)   correct answer
+
+b)   wrong answer 1
+
+c)   wrong answer 2
+part 2
+
+d)   wrong answer 3
+
+e)   wrong answer 4
+
+
+";
+
+        let expected = vec![
+            QuestionAnswer::new(1, "true or false", vec!["true", "false"], vec![0]),
+            QuestionAnswer::new(
+                2,
+                "q part1 q part2",
+                vec![
+                    "correct answer",
+                    "wrong answer 1",
+                    "wrong answer 2 part 2",
+                    "wrong answer 3",
+                    "wrong answer 4",
+                ],
+                vec![0],
+            ),
+        ];
+
+        let res = parse_pdf(raw_string.to_owned())?.questions;
+
+        assert_eq!(res, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn src_test_three_answers() -> Result<()> {
+        let raw_string = "
+
+12.    question  [12]
+
+1)  correct answer on channel 16.
+
+2)  wrong answer 1
+
+3)  wrong answer 2
+
+
+
+ 13.
+        ";
+
+        let expected = vec![QuestionAnswer::new(
+            12,
+            "question",
+            vec![
+                "correct answer on channel 16.",
+                "wrong answer 1",
+                "wrong answer 2",
+            ],
+            vec![0],
+        )];
+
+        let res = parse_pdf(raw_string.to_owned())?.questions;
+
+        assert_eq!(res, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn src_test_sections() -> Result<()> {
+        let raw_string = "
+I. Allgemeines
+
+ 1.   question 1   [1]
+1)  correct answer
+2)  wrong answer
+
+ 2.   question 2   [2]
+1)  correct answer
+2)  wrong answer
+
+II.
+Betriebsverfahren
+
+ 3.   question 3   [3]
+1)  correct answer
+2)  wrong answer
+        ";
+
+        let catalog = parse_pdf(raw_string.to_owned())?;
+        assert_eq!(catalog.format, CatalogFormat::Src);
+        let sections: Vec<Option<String>> =
+            catalog.questions.into_iter().map(|q| q.section).collect();
+
+        assert_eq!(
+            sections,
+            vec![
+                Some("I. Allgemeines".to_owned()),
+                Some("I. Allgemeines".to_owned()),
+                Some("II. Betriebsverfahren".to_owned()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pages() -> Result<()> {
+        let pages = [
+            "\n\n1.   first   [1]\n\na)   yes\n\nb)   no\n\n2.   second   [2]\n\na)   yes\n\nb)   no\n",
+            "\n\n3.   third   [3]\n\na)   yes\n\nb)   no\n",
+        ]
+        .map(String::from);
+
+        let pages: Vec<Option<usize>> = parse_pdf_pages(&pages)?
+            .questions
+            .into_iter()
+            .map(|q| q.page)
+            .collect();
+        assert_eq!(pages, vec![Some(1), Some(1), Some(2)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pages_without_trailing_line_break() -> Result<()> {
+        let pages = [
+            "\n12. Frage eins [12]\n1) richtig\n2) falsch",
+            "13. Frage zwei [13]\n1) richtig\n2) falsch\n",
+        ]
+        .map(String::from);
+
+        let catalog = parse_pdf_pages(&pages)?;
+        assert_eq!(catalog.format, CatalogFormat::Src);
+        let questions: Vec<(usize, Option<usize>, Vec<String>)> = catalog
+            .questions
+            .into_iter()
+            .map(|q| (q.id, q.page, q.possible_answers))
+            .collect();
+        let answers = vec!["richtig".to_owned(), "falsch".to_owned()];
+        assert_eq!(
+            questions,
+            vec![(12, Some(1), answers.clone()), (13, Some(2), answers)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_issues() -> Result<()> {
+        let s = "
+1.   first   [1]
+
+a)   yes
+
+b)   no
+
+2.   second   [7]
+
+a)   yes
+
+b)   no
+
+1.   again   [1]
+
+a)   yes
+
+b)   no
+
+3.   broken   [3]
+
+a)   only one answer
+";
+
+        let catalog = parse_pdf(s.to_owned())?;
+        let ids: Vec<usize> = catalog.questions.iter().map(|q| q.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+
+        let issues: Vec<(IssueKind, Option<usize>)> = catalog
+            .issues
+            .iter()
+            .map(|issue| (issue.kind.clone(), issue.number))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (IssueKind::NumberMismatch(7), Some(2)),
+                (IssueKind::DuplicateNumber, Some(1)),
+                (IssueKind::Unmatched, Some(3)),
+            ]
+        );
+        let broken = &catalog.issues[2];
+        assert_eq!(broken.snippet, "3.   broken   [3]\n\na)   only one answer");
+        assert_eq!(&s[broken.offset..broken.offset + 2], "3.");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_source() -> Result<()> {
+        assert_eq!(TextSource::from_arg("-"), TextSource::Stdin);
+        assert_eq!(
+            TextSource::from_arg("katalog.TXT"),
+            TextSource::Text(PathBuf::from("katalog.TXT"))
+        );
+        assert_eq!(
+            TextSource::from_arg("katalog.pdf"),
+            TextSource::Pdf(PathBuf::from("katalog.pdf"))
+        );
+
+        let text = "\n\n1.   first   [1]\n\na)   yes\n\nb)   no\n\x0c\n\n2.   second   [2]\n\na)   yes\n\nb)   no\n\x0c";
+        let pages: Vec<Option<usize>> =
+            parse_pdf_pages(&TextSource::Stdin.pages(text.as_bytes())?)?
+                .questions
+                .into_iter()
+                .map(|q| q.page)
+                .collect();
+        assert_eq!(pages, vec![Some(1), Some(2)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_title_and_edition() {
+        let s = "\n\nBundesamt\n\nGesamtfragenkatalog für das\nUKW-Sprechfunkzeugnis (UBI)\n\nStand: 01.07.2023\n\n1.   first";
+        assert_eq!(
+            extract_title(s).as_deref(),
+            Some("Gesamtfragenkatalog für das UKW-Sprechfunkzeugnis (UBI)")
+        );
+        assert_eq!(extract_edition(s).as_deref(), Some("01.07.2023"));
+
+        assert_eq!(
+            extract_edition("Ausgabe Juli 2023").as_deref(),
+            Some("Juli 2023")
+        );
+        assert_eq!(extract_title("1. Frage"), None);
+        assert_eq!(extract_edition("Stand der Technik"), None);
+    }
 }
@@ -0,0 +1,329 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use rusqlite::types::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{sql_catalog, TOTAL_COUNT_TRIES_PER_QUESTION};
+
+/// Default count of days a wrong answer stays in the [Pool::Mistakes].
+pub const DEFAULT_MISTAKE_DAYS: u32 = 14;
+
+/// Default count of the last answers, whose questions are not asked again right away.
+pub const DEFAULT_NO_REPEAT: u32 = 1;
+
+/// Sql condition on a row of `questions` with the values bound to its `?` parameters in order.
+///
+/// Values given by the user (e.g. the name of a deck) are always bound, never written into `sql`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlCondition {
+    pub sql: String,
+    pub params: Vec<Value>,
+}
+
+impl SqlCondition {
+    /// Returns a condition without parameters.
+    pub fn new<S: Into<String>>(sql: S) -> Self {
+        Self {
+            sql: sql.into(),
+            params: vec![],
+        }
+    }
+
+    /// Returns a condition, whose `?` parameters are bound to `params`.
+    pub fn with_params<S: Into<String>>(sql: S, params: Vec<Value>) -> Self {
+        Self {
+            sql: sql.into(),
+            params,
+        }
+    }
+
+    /// Returns the condition fulfilled if both `self` and `other` are.
+    pub fn and(mut self, other: SqlCondition) -> Self {
+        self.sql = format!("({}) AND ({})", self.sql, other.sql);
+        self.params.extend(other.params);
+        self
+    }
+}
+
+/// Describes which questions are asked.
+///
+/// It is translated into sql conditions on the table `questions` by the [DB](crate::db::DB).
+///
+/// ```
+/// let selection = Selection::default();
+/// let q = db.next_question(&selection)?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Selection {
+    pub pool: Pool,
+    pub filter: Filter,
+    pub order: Order,
+    /// Questions of the last `no_repeat` answers are skipped, as long as there are other questions left.
+    pub no_repeat: u32,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self {
+            pool: Pool::default(),
+            filter: Filter::default(),
+            order: Order::default(),
+            no_repeat: DEFAULT_NO_REPEAT,
+        }
+    }
+}
+
+impl Selection {
+    /// Returns the sql condition a row of `questions` has to fulfill to be asked.
+    pub fn sql_condition(&self) -> SqlCondition {
+        self.pool.sql_condition().and(self.filter.sql_condition())
+    }
+
+    /// Returns the sql condition excluding the questions of the last `no_repeat` answers.
+    pub fn sql_no_repeat_condition(&self) -> SqlCondition {
+        SqlCondition::with_params(
+            "questions.id NOT IN (SELECT question_id
+                        FROM history
+                        ORDER BY id DESC
+                        LIMIT ?)",
+            vec![Value::from(self.no_repeat)],
+        )
+    }
+}
+
+/// Restricts the questions, which are asked and counted for the progress.
+///
+/// The id range and the sections define the subset of the catalog the progress is measured on.
+/// `progress` only decides, which questions of this subset are asked.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Filter {
+    /// Smallest id of asked questions.
+    pub from: Option<usize>,
+    /// Biggest id of asked questions.
+    pub to: Option<usize>,
+    /// Roman numerals of the asked sections (e.g. `II`). All sections if empty.
+    pub sections: Vec<String>,
+    pub progress: Progress,
+}
+
+impl Filter {
+    /// Returns the sql condition a row of `questions` has to fulfill to be asked.
+    pub fn sql_condition(&self) -> SqlCondition {
+        self.sql_subset_condition()
+            .and(self.progress.sql_condition())
+    }
+
+    /// Returns the sql condition a row of `questions` has to fulfill to be counted for the progress.
+    ///
+    /// A question is in a section, if its `section` starts with the numeral followed by a dot.
+    pub fn sql_subset_condition(&self) -> SqlCondition {
+        let mut condition = SqlCondition::new("1");
+        if let Some(from) = self.from {
+            condition = condition.and(SqlCondition::with_params(
+                "questions.id >= ?",
+                vec![Value::from(from as i64)],
+            ));
+        }
+        if let Some(to) = self.to {
+            condition = condition.and(SqlCondition::with_params(
+                "questions.id <= ?",
+                vec![Value::from(to as i64)],
+            ));
+        }
+        if !self.sections.is_empty() {
+            condition = condition.and(SqlCondition::with_params(
+                vec!["instr(section, ?) = 1"; self.sections.len()].join(" OR "),
+                self.sections
+                    .iter()
+                    .map(|numeral| Value::from(format!("{numeral}.")))
+                    .collect(),
+            ));
+        }
+        condition
+    }
+
+    /// Describes the filter for the user. Returns `None` for the default filter.
+    pub fn name(&self) -> Option<String> {
+        let mut parts = vec![];
+        match (self.from, self.to) {
+            (Some(from), Some(to)) => parts.push(format!("Fragen {from}–{to}")),
+            (Some(from), None) => parts.push(format!("ab Frage {from}")),
+            (None, Some(to)) => parts.push(format!("bis Frage {to}")),
+            (None, None) => {}
+        }
+        if !self.sections.is_empty() {
+            parts.push(format!("Abschnitt {}", self.sections.join(", ")));
+        }
+        match self.progress {
+            Progress::New => parts.push("nur neue".to_owned()),
+            Progress::Open => {}
+            Progress::Mastered => parts.push("nur gemeisterte".to_owned()),
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+/// Checks if `s` is a roman numeral as used for sections (e.g. `IV`).
+pub fn is_roman_numeral(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| matches!(c, 'I' | 'V' | 'X'))
+}
+
+/// Which questions are asked regarding their `question progress`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Progress {
+    /// Questions never answered.
+    New,
+    /// Questions not yet answered correctly [TOTAL_COUNT_TRIES_PER_QUESTION] times.
+    #[default]
+    Open,
+    /// Questions already answered correctly [TOTAL_COUNT_TRIES_PER_QUESTION] times.
+    Mastered,
+}
+
+impl Progress {
+    /// Returns the sql condition a row of `questions` has to fulfill to be asked.
+    pub fn sql_condition(&self) -> SqlCondition {
+        SqlCondition::new(match self {
+            Progress::New => "correctly_answered = 0
+                 AND questions.id NOT IN (SELECT question_id FROM history)"
+                .to_owned(),
+            Progress::Open => format!("correctly_answered < {TOTAL_COUNT_TRIES_PER_QUESTION}"),
+            Progress::Mastered => format!("correctly_answered >= {TOTAL_COUNT_TRIES_PER_QUESTION}"),
+        })
+    }
+}
+
+/// Set of questions the user trains.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pool {
+    /// All questions of the [Filter].
+    #[default]
+    All,
+    /// Questions answered wrong within the last `days` days and not answered correctly since ("Fehlerkorb").
+    Mistakes { days: u32 },
+    /// Questions bookmarked by the user.
+    Bookmarks,
+    /// Custom questions of `deck` or of every deck.
+    Custom { deck: Option<String> },
+}
+
+impl Pool {
+    /// Returns the sql condition a row of `questions` has to fulfill to be in this pool.
+    pub fn sql_condition(&self) -> SqlCondition {
+        match self {
+            Pool::All => SqlCondition::new("1"),
+            Pool::Mistakes { days } => SqlCondition::with_params(
+                "questions.id IN (SELECT question_id
+                        FROM history
+                        GROUP BY question_id
+                        HAVING max(CASE WHEN NOT correct THEN answered_at END) >= datetime('now', ?)
+                           AND max(CASE WHEN NOT correct THEN id END) > coalesce(max(CASE WHEN correct THEN id END), 0))",
+                vec![Value::from(format!("-{days} days"))],
+            ),
+            Pool::Bookmarks => SqlCondition::new(format!(
+                "questions.id IN (SELECT number FROM notes WHERE bookmarked AND catalog = {})",
+                sql_catalog("notes.number")
+            )),
+            Pool::Custom { deck: None } => SqlCondition::new("custom_deck IS NOT NULL"),
+            Pool::Custom { deck: Some(deck) } => {
+                SqlCondition::with_params("custom_deck = ?", vec![Value::from(deck.clone())])
+            }
+        }
+    }
+
+    /// Name of the pool shown to the user.
+    pub fn name(&self) -> String {
+        match self {
+            Pool::All => "Alle Fragen".to_owned(),
+            Pool::Mistakes { days } => format!("Fehlerkorb ({days} Tage)"),
+            Pool::Bookmarks => "Lesezeichen".to_owned(),
+            Pool::Custom { deck: None } => "Eigene Fragen".to_owned(),
+            Pool::Custom { deck: Some(deck) } => format!("Sammlung {deck}"),
+        }
+    }
+}
+
+/// Order in which the questions of a [Pool] are asked.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Order {
+    /// Every question is drawn at random, independent of the questions before.
+    #[default]
+    Random,
+    /// Questions are drawn from a shuffled deck ("Stapel"), so every question is asked once per round.
+    ///
+    /// The deck is saved in the table `deck` and thus survives restarts.
+    Deck,
+    /// Questions are asked by id, continuing after the last answered question.
+    Sequential,
+    /// Questions with the highest error rate come first. Unseen questions count as half wrong.
+    Weakest,
+    /// Questions never answered come first, then those not answered for the longest time.
+    Unseen,
+    /// Questions are asked section by section in order of the catalog, at random within a section.
+    Section,
+}
+
+impl Order {
+    /// Every order in the sequence shown to the user.
+    pub const ALL: [Order; 6] = [
+        Order::Random,
+        Order::Deck,
+        Order::Sequential,
+        Order::Weakest,
+        Order::Unseen,
+        Order::Section,
+    ];
+
+    /// Name of the order shown to the user.
+    pub fn name(&self) -> String {
+        match self {
+            Order::Random => "Zufällig",
+            Order::Deck => "Stapel",
+            Order::Sequential => "Der Reihe nach",
+            Order::Weakest => "Schwächste zuerst",
+            Order::Unseen => "Ungesehene zuerst",
+            Order::Section => "Nach Abschnitt",
+        }
+        .to_owned()
+    }
+
+    /// Returns the sql `ORDER BY` clause for rows of `questions` joined with `deck`.
+    pub fn sql_order_by(&self) -> &'static str {
+        match self {
+            Order::Random => "RANDOM()",
+            Order::Deck => "position",
+            Order::Sequential => {
+                "questions.id <= coalesce((SELECT question_id FROM history ORDER BY id DESC LIMIT 1), 0),
+                 questions.id"
+            }
+            Order::Weakest => {
+                "(SELECT (total(NOT correct) + 1.0) / (count() + 2)
+                    FROM history
+                    WHERE question_id = questions.id) DESC,
+                 RANDOM()"
+            }
+            Order::Unseen => {
+                "(SELECT max(id) FROM history WHERE question_id = questions.id),
+                 RANDOM()"
+            }
+            Order::Section => {
+                "(SELECT min(s.id) FROM questions AS s WHERE s.section IS questions.section),
+                 RANDOM()"
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use color_eyre::eyre::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::app::{AnswerMode, App, Scoring};
+use crate::db::DB;
+use crate::selection::Selection;
+use crate::textinput::TextInput;
+
+/// State of a learning session, which is saved on exit and can be resumed on the next start.
+///
+/// It holds everything needed to show the user the exact screen he left:
+/// the settings of the session, the current question with its order of answers and the answer given so far.
+///
+/// ```
+/// db.save_session(&Session::from_app(&app))?;
+/// let app = db.load_session()?.unwrap().into_app(&db)?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub selection: Selection,
+    pub scoring: Scoring,
+    pub answer_mode: AnswerMode,
+    pub mistake_days: u32,
+    pub question_id: usize,
+    /// [QuestionAnswer::order](crate::app::QuestionAnswer::order) of the current question.
+    pub answer_order: Vec<usize>,
+    pub marked: Vec<usize>,
+    pub user_answers: Option<Vec<usize>>,
+    /// Highlighted answer in [AnswerMode::Choice].
+    pub selected: Option<usize>,
+    /// Answer typed in [AnswerMode::FreeRecall].
+    pub text: String,
+    pub revealed: bool,
+}
+
+impl Session {
+    pub fn from_app(app: &App) -> Self {
+        let q = &app.question_answer;
+        Self {
+            selection: app.selection.clone(),
+            scoring: app.scoring,
+            answer_mode: app.answer_mode,
+            mistake_days: app.mistake_days,
+            question_id: q.id,
+            answer_order: q.order.clone(),
+            marked: q.marked.clone(),
+            user_answers: q.user_answers.clone(),
+            selected: app.item_list_state.selected(),
+            text: app.text_input.value().to_owned(),
+            revealed: app.revealed,
+        }
+    }
+
+    /// Builds the [App] of this session with the current question and progress loaded from `db`.
+    ///
+    /// Returns `None` if the question does not exist anymore or its answers changed.
+    pub fn into_app(self, db: &DB) -> Result<Option<App>> {
+        let Some(mut q) = db.get_question(self.question_id)? else {
+            return Ok(None);
+        };
+        if !q.is_permutation(&self.answer_order)
+            || self
+                .marked
+                .iter()
+                .chain(self.user_answers.iter().flatten())
+                .any(|&i| i >= q.possible_answers.len())
+        {
+            return Ok(None);
+        }
+        q.reorder(&self.answer_order);
+        q.marked = self.marked;
+        q.user_answers = self.user_answers;
+
+        let mut app = App::new(
+            q,
+            db.get_total_progress(&self.selection.filter)?,
+            db.get_total_question_count(&self.selection.filter)?,
+        );
+        app.selection = self.selection;
+        app.scoring = self.scoring;
+        app.answer_mode = self.answer_mode;
+        app.mistake_days = self.mistake_days;
+        app.item_list_state.select(self.selected);
+        app.text_input = TextInput::new(self.text);
+        app.revealed = self.revealed;
+
+        Ok(Some(app))
+    }
+
+    /// Short description of the session for the user, e.g. `Frage 12, Fehlerkorb (14 Tage)`.
+    pub fn description(&self) -> String {
+        let mut parts = vec![
+            format!("Frage {}", self.question_id),
+            self.selection.pool.name(),
+        ];
+        if let Some(filter) = self.selection.filter.name() {
+            parts.push(filter);
+        }
+        parts.join(", ")
+    }
+}
@@ -0,0 +1,159 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::time::{Duration, Instant};
+
+use crate::db::TOTAL_COUNT_TRIES_PER_QUESTION;
+
+/// Statistics of the current learning session, shown in the summary on exit.
+///
+/// ```
+/// let mut stats = SessionStats::default();
+/// stats.record(12, false, 2, 0);
+/// assert_eq!(stats.missed, vec![12]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SessionStats {
+    pub started: Instant,
+    /// Count of given answers.
+    pub answered: usize,
+    /// Count of given answers, which were correct.
+    pub correct: usize,
+    /// Ids of questions, which reached the max `question progress` in this session.
+    pub mastered: Vec<usize>,
+    /// Ids of questions answered wrong in this session, in order of the first mistake.
+    pub missed: Vec<usize>,
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            answered: 0,
+            correct: 0,
+            mastered: Vec::new(),
+            missed: Vec::new(),
+        }
+    }
+}
+
+impl SessionStats {
+    /// Counts the answer to question `id`, which changed its `question progress` from `old_count` to `new_count`.
+    pub fn record(&mut self, id: usize, correct: bool, old_count: usize, new_count: usize) {
+        self.answered += 1;
+        if correct {
+            self.correct += 1;
+        } else if !self.missed.contains(&id) {
+            self.missed.push(id);
+        }
+        if old_count < TOTAL_COUNT_TRIES_PER_QUESTION
+            && new_count >= TOTAL_COUNT_TRIES_PER_QUESTION
+            && !self.mastered.contains(&id)
+        {
+            self.mastered.push(id);
+        }
+    }
+
+    /// Returns the share of correct answers in percent or `None` if nothing was answered.
+    pub fn accuracy(&self) -> Option<usize> {
+        (self.answered > 0).then(|| (self.correct * 100 + self.answered / 2) / self.answered)
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Returns the summary as pairs of label and value.
+    pub fn lines(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Beantwortet", self.answered.to_string()),
+            (
+                "Richtig",
+                match self.accuracy() {
+                    Some(accuracy) => format!("{} ({accuracy} %)", self.correct),
+                    None => "-".to_owned(),
+                },
+            ),
+            ("Neu gelernt", self.mastered.len().to_string()),
+            ("Lernzeit", format_duration(self.elapsed())),
+            ("Wiederholen", format_ids(&self.missed)),
+        ]
+    }
+
+    /// Returns the summary in one line, e.g. for printing it after the TUI is closed.
+    pub fn compact(&self) -> String {
+        let mut s = format!(
+            "{} Fragen beantwortet, {} richtig, {} neu gelernt in {}.",
+            self.answered,
+            match self.accuracy() {
+                Some(accuracy) => format!("{accuracy} %"),
+                None => "-".to_owned(),
+            },
+            self.mastered.len(),
+            format_duration(self.elapsed())
+        );
+        if !self.missed.is_empty() {
+            s += &format!(" Wiederholen: {}", format_ids(&self.missed));
+        }
+        s
+    }
+}
+
+/// Formats `d` as `h:mm:ss`.
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Formats question ids as comma separated list or `-` if there are none.
+fn format_ids(ids: &[usize]) -> String {
+    if ids.is_empty() {
+        return "-".to_owned();
+    }
+    ids.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_record() {
+        let mut stats = SessionStats::default();
+        assert_eq!(stats.accuracy(), None);
+
+        stats.record(1, true, 2, 3);
+        stats.record(2, false, 1, 0);
+        stats.record(2, false, 0, 0);
+        stats.record(3, true, 3, 3);
+
+        assert_eq!(stats.answered, 4);
+        assert_eq!(stats.accuracy(), Some(50));
+        assert_eq!(stats.mastered, vec![1]);
+        assert_eq!(stats.missed, vec![2]);
+        assert!(stats.compact().ends_with("Wiederholen: 2"));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(59)), "0:00:59");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+    }
+}
@@ -0,0 +1,241 @@
+/**
+ * ubilerntui
+ * Copyright (C) 2026 Adam McKellar
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// State of a text field the user can type into.
+///
+/// The cursor is saved as index of chars (not bytes) into `value`.
+/// Line breaks are allowed, so the same struct serves single and multi-line inputs.
+///
+/// ```
+/// let mut input = TextInput::default();
+/// input.insert('a');
+/// input.insert('b');
+/// input.left();
+/// input.backspace();
+/// assert_eq!(input.value(), "b");
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+}
+
+impl TextInput {
+    /// Returns [TextInput] holding `value` with the cursor at its end.
+    pub fn new<S: ToString>(value: S) -> Self {
+        let value = value.to_string();
+        let cursor = value.chars().count();
+        Self { value, cursor }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Empties the input.
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+    }
+
+    /// Inserts `c` at the position of the cursor and moves the cursor behind it.
+    pub fn insert(&mut self, c: char) {
+        let i = self.byte_index();
+        self.value.insert(i, c);
+        self.cursor += 1;
+    }
+
+    /// Removes the char before the cursor.
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let i = self.byte_index();
+            self.value.remove(i);
+        }
+    }
+
+    /// Removes the char under the cursor.
+    pub fn delete(&mut self) {
+        if self.cursor < self.value.chars().count() {
+            let i = self.byte_index();
+            self.value.remove(i);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.value.chars().count());
+    }
+
+    /// Moves the cursor to the start of the current line.
+    pub fn home(&mut self) {
+        self.cursor -= self.cursor_line_col().1;
+    }
+
+    /// Moves the cursor to the end of the current line.
+    pub fn end(&mut self) {
+        let rest = self.value.chars().skip(self.cursor);
+        self.cursor += rest.take_while(|&c| c != '\n').count();
+    }
+
+    /// Returns line and column of the cursor, both starting at `0`.
+    pub fn cursor_line_col(&self) -> (usize, usize) {
+        let before: Vec<char> = self.value.chars().take(self.cursor).collect();
+        let line = before.iter().filter(|&&c| c == '\n').count();
+        let col = before.iter().rev().take_while(|&&c| c != '\n').count();
+        (line, col)
+    }
+
+    /// Returns the lines of the input as displayed in a box `width` chars wide and the line and column
+    /// of the cursor within them.
+    ///
+    /// Lines longer than `width` are broken after `width` chars. A cursor behind a full line stays on
+    /// that line at column `width`.
+    pub fn wrapped_lines(&self, width: usize) -> (Vec<String>, (usize, usize)) {
+        let width = width.max(1);
+        let mut lines = Vec::new();
+        let mut cursor = (0, 0);
+        let mut start = 0;
+        for line in self.value.split('\n') {
+            let chars: Vec<char> = line.chars().collect();
+            if (start..=start + chars.len()).contains(&self.cursor) {
+                let col = self.cursor - start;
+                cursor = if col > 0 && col.is_multiple_of(width) {
+                    (lines.len() + col / width - 1, width)
+                } else {
+                    (lines.len() + col / width, col % width)
+                };
+            }
+            if chars.is_empty() {
+                lines.push(String::new());
+            }
+            lines.extend(
+                chars
+                    .chunks(width)
+                    .map(|chunk| chunk.iter().collect::<String>()),
+            );
+            start += chars.len() + 1;
+        }
+        (lines, cursor)
+    }
+
+    /// Applies the editing keys (chars, backspace, delete, arrows, home and end) to the input.
+    ///
+    /// Returns `false` if the key is not an editing key, e.g. enter or escape, which are left to the caller.
+    pub fn handle_key(&mut self, key: &KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.insert(c)
+            }
+            KeyCode::Backspace => self.backspace(),
+            KeyCode::Delete => self.delete(),
+            KeyCode::Left => self.left(),
+            KeyCode::Right => self.right(),
+            KeyCode::Home => self.home(),
+            KeyCode::End => self.end(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn byte_index(&self) -> usize {
+        self.value
+            .char_indices()
+            .nth(self.cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.value.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_insert_and_delete_multibyte() {
+        let mut input = TextInput::new("Fähre");
+        input.left();
+        input.left();
+        input.left();
+        input.backspace();
+        assert_eq!(input.value(), "Fhre");
+        input.insert('ä');
+        input.delete();
+        assert_eq!(input.value(), "Färe");
+        assert_eq!(input.cursor_line_col(), (0, 2));
+    }
+
+    #[test]
+    fn test_multi_line_cursor() {
+        let mut input = TextInput::new("ab\ncde");
+        assert_eq!(input.cursor_line_col(), (1, 3));
+        input.home();
+        assert_eq!(input.cursor_line_col(), (1, 0));
+        input.left();
+        assert_eq!(input.cursor_line_col(), (0, 2));
+        input.home();
+        input.end();
+        assert_eq!(input.cursor_line_col(), (0, 2));
+        input.insert('\n');
+        assert_eq!(input.value(), "ab\n\ncde");
+        assert_eq!(input.cursor_line_col(), (1, 0));
+    }
+
+    #[test]
+    fn test_wrapped_lines() {
+        let mut input = TextInput::new("abcdef\n\nghä");
+        assert_eq!(
+            input.wrapped_lines(3),
+            (
+                vec!["abc".into(), "def".into(), "".into(), "ghä".into()],
+                (3, 3)
+            )
+        );
+        input.home();
+        assert_eq!(input.wrapped_lines(3).1, (3, 0));
+        input.left();
+        assert_eq!(input.wrapped_lines(3).1, (2, 0));
+        input.left();
+        assert_eq!(input.wrapped_lines(3).1, (1, 3));
+        input.left();
+        assert_eq!(input.wrapped_lines(3).1, (1, 2));
+        input.clear();
+        assert_eq!(input.wrapped_lines(3), (vec!["".into()], (0, 0)));
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut input = TextInput::default();
+        assert!(input.handle_key(&KeyEvent::from(KeyCode::Char('q'))));
+        assert!(!input.handle_key(&KeyEvent::from(KeyCode::Enter)));
+        assert!(!input.handle_key(&KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL)));
+        assert_eq!(input.value(), "q");
+    }
+}
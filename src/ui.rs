@@ -14,8 +14,6 @@
  * You should have received a copy of the GNU General Public License
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use std::io::Cursor;
-
 use ratatui::{
     layout::Alignment,
     prelude::{Constraint, Direction, Frame, Layout, Rect, Span, Style, Text},
@@ -27,11 +25,24 @@ use ratatui::{
     },
 };
 
-use hyphenation::{Load, Standard};
 use once_cell::sync::Lazy;
 use textwrap;
 
-use crate::app::{App, QuestionAnswer};
+use crate::app::{AnswerMode, App, Evaluation, QuestionAnswer, Screen};
+use crate::custom::custom_number;
+use crate::dictionary::DICTIONARY;
+use crate::menu;
+use crate::selection::Pool;
+
+static TEXTWRAP_DICT: Lazy<textwrap::WordSplitter> =
+    Lazy::new(|| textwrap::WordSplitter::Hyphenation(DICTIONARY.clone()));
+
+/// Wraps `s` to lines of `width` with hyphenation, joins the lines with `line_end` and returns them with the count of lines.
+fn wrap_text_count(s: &str, width: usize, line_end: &str) -> (String, usize) {
+    let textwrap_options = textwrap::Options::new(width).word_splitter((*TEXTWRAP_DICT).clone());
+    let s_vec = textwrap::wrap(s, &textwrap_options);
+    (s_vec.join(line_end), s_vec.len())
+}
 
 /// Renders to screen. UI Part.
 ///
@@ -50,7 +61,24 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         ])
         .split(area);
 
-    render_title_and_question(frame, chunks[0], &app.question_answer);
+    let title = match (&app.status, app.finished_message()) {
+        (_, Some(message)) => message.to_owned(),
+        (Some(status), None) => status.clone(),
+        (None, None) => {
+            let mut title = app
+                .catalog_name
+                .clone()
+                .unwrap_or_else(|| "UBI Lern TUI".to_owned());
+            if app.selection.pool != Pool::All {
+                title += &format!(" · {}", app.selection.pool.name());
+            }
+            if let Some(filter) = app.selection.filter.name() {
+                title += &format!(" · {filter}");
+            }
+            title
+        }
+    };
+    render_title_and_question(frame, chunks[0], &title, &app.question_answer);
 
     render_total_progress(
         frame,
@@ -61,35 +89,143 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
 
     render_question_progress(frame, chunks[2], &app.question_answer);
 
-    render_selector_list(
-        frame,
-        chunks[3],
-        &app.question_answer,
-        &mut app.item_list_state,
-    );
+    match app.screen {
+        Screen::Quiz => {}
+        Screen::Menu => {
+            render_menu(frame, chunks[3], app);
+            let mut bottom_help_bar_text = vec![
+                "(tab)/(esc) Zurück",
+                "(w) Hoch",
+                "(s) Runter",
+                "(e) Auswählen",
+            ];
+            render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
+            return;
+        }
+        Screen::Edit(target) => {
+            render_editor(frame, chunks[3], app, target.name());
+            let mut bottom_help_bar_text = vec![
+                "(esc) Speichern",
+                "(ctrl+q) Verwerfen",
+                "(enter) Neue Zeile",
+            ];
+            render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
+            return;
+        }
+        Screen::Summary => {
+            render_summary(frame, chunks[3], app);
+            let mut bottom_help_bar_text = vec!["(q)/(e) Beenden"];
+            if !app.finished {
+                bottom_help_bar_text.push("(z) Weiterlernen");
+            }
+            if app.undo.is_some() {
+                bottom_help_bar_text.push("(u) Rückgängig");
+            }
+            render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
+            return;
+        }
+    }
 
-    if app.question_answer.user_answer.is_none() {
-        let mut bottom_help_bar_text = vec![
-            "(q)/(esc) Beenden",
-            "(w) Hoch",
-            "(s) Runter",
-            "(e) Auswählen",
-        ];
-        render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
-    } else {
-        let mut bottom_help_bar_text = vec!["(q)/(esc) Beenden", "(e) Nächste Frage"];
-        render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
+    let mut quiz_area = chunks[3];
+    if app.is_answered() {
+        let q = &app.question_answer;
+        let width = (area.width - 6).into();
+        let panels: Vec<(&str, (String, usize))> =
+            [(" Erklärung ", &q.explanation), (" Notiz ", &q.note)]
+                .into_iter()
+                .filter(|(_, text)| !text.is_empty())
+                .map(|(title, text)| (title, wrap_text_count(text, width, "\n")))
+                .collect();
+
+        let mut constraints = vec![Constraint::Min(1)];
+        constraints.extend(
+            panels
+                .iter()
+                .map(|(_, (_, lines))| Constraint::Length((lines + 2).try_into().unwrap())),
+        );
+        let panel_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(chunks[3]);
+        quiz_area = panel_chunks[0];
+        for ((title, (text, _)), &panel_area) in panels.into_iter().zip(&panel_chunks[1..]) {
+            render_text_panel(frame, panel_area, title, text);
+        }
+    }
+
+    match app.answer_mode {
+        AnswerMode::Choice => {
+            render_selector_list(
+                frame,
+                quiz_area,
+                &app.question_answer,
+                &mut app.item_list_state,
+            );
+
+            if app.question_answer.user_answers.is_none() {
+                let mut bottom_help_bar_text = vec![
+                    "(q)/(esc) Beenden",
+                    "(tab) Menü",
+                    "(b) Lesezeichen",
+                    "(n) Notiz",
+                    "(w) Hoch",
+                    "(s) Runter",
+                    "(e) Auswählen",
+                ];
+                if app.question_answer.has_multiple_right_answers() {
+                    bottom_help_bar_text.insert(6, "(Leertaste) Markieren");
+                }
+                render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
+            } else {
+                let mut bottom_help_bar_text = vec![
+                    "(q)/(esc) Beenden",
+                    "(tab) Menü",
+                    "(b) Lesezeichen",
+                    "(n) Notiz",
+                    "(x) Erklärung",
+                    "(k) Korrigieren",
+                    "(e) Nächste Frage",
+                ];
+                if app.undo.is_some() {
+                    bottom_help_bar_text.insert(5, "(u) Rückgängig");
+                }
+                render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
+            }
+        }
+        AnswerMode::FreeRecall => {
+            render_free_recall(frame, quiz_area, app);
+
+            if app.revealed {
+                let mut bottom_help_bar_text = vec![
+                    "(q)/(esc) Beenden",
+                    "(b) Lesezeichen",
+                    "(n) Notiz",
+                    "(1) Nochmal",
+                    "(2) Schwer",
+                    "(3) Gut",
+                    "(4) Leicht",
+                ];
+                render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
+            } else {
+                let mut bottom_help_bar_text =
+                    vec!["(esc) Beenden", "(tab) Menü", "(enter) Aufdecken"];
+                if app.undo.is_some() {
+                    bottom_help_bar_text.insert(2, "(strg+z) Rückgängig");
+                }
+                render_bottom_help_bar(frame, chunks[4], &mut bottom_help_bar_text);
+            }
+        }
     }
 }
 
 /// Top part of TUI. (title and number of question)
-fn render_title_and_question(frame: &mut Frame, area: Rect, q: &QuestionAnswer) {
+fn render_title_and_question(frame: &mut Frame, area: Rect, title: &str, q: &QuestionAnswer) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(area);
 
-    let title = Paragraph::new(Text::styled("UBI Lern TUI", Style::default())).block(
+    let title = Paragraph::new(Text::styled(title, Style::default())).block(
         Block::default()
             .borders(Borders::LEFT | Borders::TOP | Borders::BOTTOM)
             .border_type(BorderType::Rounded)
@@ -97,7 +233,17 @@ fn render_title_and_question(frame: &mut Frame, area: Rect, q: &QuestionAnswer)
     );
     frame.render_widget(title, chunks[0]);
 
-    let question_number = format!("Frage {}", q.id);
+    let mut question_number = match (&q.custom_deck, &q.section) {
+        (Some(deck), _) => format!("{deck} · Eigene Frage {}", custom_number(q.id)),
+        (None, Some(section)) => format!("{section} · Frage {}", q.id),
+        (None, None) => format!("Frage {}", q.id),
+    };
+    if let Some(page) = q.page {
+        question_number += &format!(" · Katalog S. {page}");
+    }
+    if q.bookmarked {
+        question_number.insert_str(0, "★ ");
+    }
     let question = Paragraph::new(Text::styled(question_number, Style::default()))
         .alignment(Alignment::Right)
         .block(
@@ -159,7 +305,9 @@ fn render_question_progress(frame: &mut Frame, area: Rect, q: &QuestionAnswer) {
 }
 
 /// Renders selector question and list where user can choose an answer.
-/// If the user chose one this function displays if the answer was correct and the right answer if not.
+/// If the user chose one this function displays if the answer was correct and the right answers if not.
+///
+/// For questions with multiple right answers checkboxes show the marked answers.
 fn render_selector_list(
     frame: &mut Frame,
     area: Rect,
@@ -169,35 +317,9 @@ fn render_selector_list(
     let style_correct = Style::default().fg(Color::Black).bg(Color::Green);
     let style_wrong = Style::default().fg(Color::Black).bg(Color::Red);
 
-    static TEXTWRAP_DICT: Lazy<textwrap::WordSplitter> = Lazy::new(|| {
-        let bytes = include_bytes!("../dictionary/de-1996.standard.bincode");
-        let mut curs = Cursor::new(bytes);
-        textwrap::WordSplitter::Hyphenation(Standard::any_from_reader(&mut curs).unwrap())
-    });
-
-    let textwrap_options =
-        textwrap::Options::new((area.width - 9).into()).word_splitter((*TEXTWRAP_DICT).clone());
-
-    macro_rules! wrap_text_count {
-        ($line_end:literal $s:expr) => {{
-            let s_vec = textwrap::wrap($s, &textwrap_options);
-            let mut s_string: String = "".to_owned();
-            for s_elem in s_vec.iter().take(s_vec.len() - 1) {
-                s_string.push_str(s_elem);
-                s_string.push_str($line_end);
-            }
-            s_string.push_str(s_vec.last().unwrap());
-            (s_string, s_vec.len())
-        }};
-    }
+    let width = (area.width - 9).into();
 
-    macro_rules! wrap_text {
-            ($s:expr) => {
-                  wrap_text_count!("\n   " $s).0
-            };
-      }
-
-    let (question_str, question_line_breaks) = wrap_text_count!("\n" q.question.as_str());
+    let (question_str, question_line_breaks) = wrap_text_count(&q.question, width, "\n");
     let question = Paragraph::new(question_str).block(
         Block::default()
             .borders(Borders::LEFT | Borders::RIGHT | Borders::TOP)
@@ -216,25 +338,35 @@ fn render_selector_list(
     frame.render_widget(question, chunks[0]);
 
     let mut list_items = Vec::<ListItem>::new();
-    const ABCD: &str = "abcd";
+
+    let show_marks = q.has_multiple_right_answers() || !q.marked.is_empty();
 
     macro_rules! abcd_i {
         ($s:expr, $i:expr) => {{
-            let list_item_string = String::from(ABCD.chars().nth($i).unwrap()) + ") " + $s;
-            ListItem::new(String::from(wrap_text!(&list_item_string)))
+            let mark = match (show_marks, q.marked.contains(&$i)) {
+                (false, _) => "",
+                (true, false) => "[ ] ",
+                (true, true) => "[x] ",
+            };
+            let list_item_string = String::from(mark) + &answer_label($i) + ") " + $s;
+            ListItem::new(wrap_text_count(&list_item_string, width, "\n   ").0)
         }};
     }
 
-    if let Some(user_answer) = q.user_answer {
+    let mut hint = "";
+    if let Some(user_answers) = &q.user_answers {
         for (i, e) in q.possible_answers.iter().enumerate() {
-            if i == q.right_answer {
+            if q.right_answers.contains(&i) {
                 list_items.push(abcd_i!(e, i).style(style_correct));
-            } else if i == user_answer {
+            } else if user_answers.contains(&i) {
                 list_items.push(abcd_i!(e, i).style(style_wrong));
             } else {
                 list_items.push(abcd_i!(e, i));
             }
         }
+        if q.evaluate() == Evaluation::PartiallyCorrect {
+            hint = " Teilweise richtig ";
+        }
     } else {
         list_items = q
             .possible_answers
@@ -242,13 +374,17 @@ fn render_selector_list(
             .enumerate()
             .map(|(i, s)| abcd_i!(s, i))
             .collect();
+        if q.has_multiple_right_answers() {
+            hint = " Mehrere Antworten richtig ";
+        }
     }
 
     let selector_list = List::new(list_items)
         .block(
             Block::default()
                 .borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM)
-                .border_type(BorderType::Rounded),
+                .border_type(BorderType::Rounded)
+                .title_bottom(hint),
         )
         .style(Style::default())
         .highlight_style(Style::default().fg(Color::Black).bg(Color::LightYellow))
@@ -258,6 +394,180 @@ fn render_selector_list(
     frame.render_stateful_widget(selector_list, chunks[1], item_list_state);
 }
 
+/// Renders the [menu](crate::menu) in place of the question.
+fn render_menu(frame: &mut Frame, area: Rect, app: &mut App) {
+    let items: Vec<ListItem> = menu::entries(app)
+        .into_iter()
+        .map(|entry| ListItem::new(entry.label))
+        .collect();
+
+    let menu = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .padding(Padding::horizontal(2))
+                .title(" Menü "),
+        )
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::LightYellow))
+        .highlight_spacing(HighlightSpacing::Always)
+        .highlight_symbol(">>");
+
+    frame.render_stateful_widget(menu, area, &mut app.menu_state);
+}
+
+/// Renders the question and the editor for one of its texts, `name` is shown as title.
+fn render_editor(frame: &mut Frame, area: Rect, app: &App, name: &str) {
+    let width = (area.width - 6).into();
+    let (question_str, question_line_breaks) =
+        wrap_text_count(&app.question_answer.question, width, "\n");
+    let question = Paragraph::new(question_str).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(2)),
+    );
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((question_line_breaks + 2).try_into().unwrap()),
+            Constraint::Min(3),
+        ])
+        .split(area);
+    frame.render_widget(question, chunks[0]);
+
+    let (lines, (line, col)) = app.edit_input.wrapped_lines(width);
+    let scroll = line.saturating_sub(chunks[1].height.saturating_sub(3).into());
+    let input = Paragraph::new(lines.join("\n"))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .padding(Padding::horizontal(2))
+                .title(format!(" {name} ")),
+        )
+        .scroll((scroll as u16, 0));
+    frame.render_widget(input, chunks[1]);
+
+    frame.set_cursor(
+        chunks[1].x + 3 + col as u16,
+        chunks[1].y + 1 + (line - scroll) as u16,
+    );
+}
+
+/// Renders `text` (e.g. the note of the user) in a box with `title` below the answers.
+fn render_text_panel(frame: &mut Frame, area: Rect, title: &str, text: String) {
+    let panel = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(2))
+            .title(title.to_owned()),
+    );
+    frame.render_widget(panel, area);
+}
+
+/// Renders the statistics of the session.
+fn render_summary(frame: &mut Frame, area: Rect, app: &App) {
+    let lines = app.stats.lines();
+    let label_width = lines
+        .iter()
+        .map(|(label, _)| label.len())
+        .max()
+        .unwrap_or(0);
+    let width = usize::from(area.width.saturating_sub(6)).saturating_sub(label_width + 2);
+
+    let text: Vec<String> = lines
+        .into_iter()
+        .map(|(label, value)| {
+            let (value, _) =
+                wrap_text_count(&value, width.max(1), &format!("\n{:label_width$}  ", ""));
+            format!("{label:label_width$}  {value}")
+        })
+        .collect();
+
+    let summary = Paragraph::new(text.join("\n")).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::new(2, 2, 1, 1))
+            .title(" Zusammenfassung "),
+    );
+    frame.render_widget(summary, area);
+}
+
+/// Renders the question, the text field for the users answer and, once revealed, the right answers.
+fn render_free_recall(frame: &mut Frame, area: Rect, app: &App) {
+    let q = &app.question_answer;
+    let width = (area.width - 6).into();
+
+    let (question_str, question_line_breaks) = wrap_text_count(&q.question, width, "\n");
+    let question = Paragraph::new(question_str).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(2)),
+    );
+
+    let (input_lines, (line, col)) = app.text_input.wrapped_lines(width);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((question_line_breaks + 2).try_into().unwrap()),
+            Constraint::Length((input_lines.len() + 2).try_into().unwrap()),
+            Constraint::Min(1),
+        ])
+        .split(area);
+
+    frame.render_widget(question, chunks[0]);
+
+    let input = Paragraph::new(input_lines.join("\n")).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(2))
+            .title(" Ihre Antwort "),
+    );
+    frame.render_widget(input, chunks[1]);
+
+    if !app.revealed {
+        frame.set_cursor(chunks[1].x + 3 + col as u16, chunks[1].y + 1 + line as u16);
+        return;
+    }
+
+    let style_correct = Style::default().fg(Color::Black).bg(Color::Green);
+    let right_answers: Vec<ListItem> = q
+        .right_answers
+        .iter()
+        .map(|&i| {
+            ListItem::new(wrap_text_count(&q.possible_answers[i], width, "\n").0)
+                .style(style_correct)
+        })
+        .collect();
+    let right_answers = List::new(right_answers).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .padding(Padding::horizontal(2))
+            .title(" Richtige Antwort "),
+    );
+    frame.render_widget(right_answers, chunks[2]);
+}
+
+/// Returns the label of the answer at index `i`: `a` to `z`, then `aa`, `ab` and so on.
+fn answer_label(i: usize) -> String {
+    let mut label = String::new();
+    let mut i = i + 1;
+    while i > 0 {
+        i -= 1;
+        label.insert(0, char::from(b'a' + (i % 26) as u8));
+        i /= 26;
+    }
+    label
+}
+
 /// Help bar showing key bindings.
 fn render_bottom_help_bar(frame: &mut Frame, area: Rect, text: &mut Vec<&str>) {
     if text.len() == 1 {
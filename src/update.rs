@@ -19,19 +19,34 @@ use color_eyre::eyre::Result;
 use ratatui::widgets::ListState;
 
 use crossterm::event::KeyCode::{self, Char};
+use crossterm::event::{KeyEvent, KeyModifiers};
 
-use colored::*;
-
-use crate::app::App;
-use crate::db::DB;
+use crate::app::{
+    AnswerMode, App, EditTarget, Evaluation, Grade, QuestionAnswer, Scoring, Screen, Undo,
+};
+use crate::custom::{custom_number, CustomQuestion, DEFAULT_DECK};
+use crate::db::{DB, TOTAL_COUNT_TRIES_PER_QUESTION};
 use crate::event::EventType;
+use crate::menu::{self, MenuAction};
+use crate::selection::Pool;
+use crate::textinput::TextInput;
 
 /// This function takes the user input changes the state of the TUI.
 ///
 /// There are multiple states:
 /// - The user has made no answer yet.
+///   - The user might mark multiple answers with space, before confirming them.
+///   - In [AnswerMode::FreeRecall] the user types his answer instead.
 /// - The user has made an answer.
 ///   - The user is shown wether or not his answer is correct.
+///   - In [AnswerMode::FreeRecall] the user is shown the right answers and grades himself.
+///
+/// The last answer can be taken back with ctrl+z (or u while choosing), until the next answer is given.
+/// With b the question is bookmarked, with n its note and with x its explanation is edited.
+/// With k the texts of the question and its answers are corrected, custom questions are edited directly.
+///
+/// Tab opens the [menu](crate::menu), where the user chooses what to train.
+/// Quitting shows the [summary](Screen::Summary) of the session first.
 ///
 /// In essence [App] is the state and [update] is the logic changing the state following the users input.
 ///
@@ -41,58 +56,472 @@ pub fn update(event: EventType, app: &mut App, db: &DB) -> Result<()> {
     match event {
         EventType::Resize(_, _) => {}
         EventType::Mouse(_) => {}
-        EventType::Key(key_event) => match app.question_answer.user_answer {
-            Some(_) => match key_event.code {
-                Char('q') | KeyCode::Esc => app.exit = true,
-                Char('e') | KeyCode::Enter => {
-                    app.question_answer.user_answer = None;
-                    app.item_list_state.select(None);
-                    if let Ok(q) = db.get_random() {
-                        app.question_answer = q;
-                        app.question_answer.scramble(&mut app.rng);
+        EventType::Tick => {}
+        EventType::Terminate(_) => app.exit = true,
+        EventType::Error(err) => return Err(err),
+        EventType::Key(key_event) => {
+            app.status = None;
+            let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+            // While the user types an answer, letters are shortcuts only together with ctrl.
+            let shortcut = ctrl || app.answer_mode == AnswerMode::Choice || app.revealed;
+            match (app.screen, key_event.code) {
+                (Screen::Menu, _) => update_menu(key_event, app, db)?,
+                (Screen::Summary, _) => update_summary(key_event, app, db)?,
+                (Screen::Edit(target), _) => update_edit(key_event, app, db, target)?,
+                (Screen::Quiz, Char('z')) if ctrl => undo(app, db)?,
+                (Screen::Quiz, Char('u')) if app.answer_mode == AnswerMode::Choice => {
+                    undo(app, db)?
+                }
+                (Screen::Quiz, Char('b')) if shortcut => {
+                    let q = &mut app.question_answer;
+                    q.bookmarked = !q.bookmarked;
+                    db.set_bookmark(q.id, q.bookmarked)?;
+                }
+                (Screen::Quiz, Char('n')) if shortcut => {
+                    app.edit_input = TextInput::new(&app.question_answer.note);
+                    app.screen = Screen::Edit(EditTarget::Note);
+                }
+                (Screen::Quiz, Char('k')) if shortcut => {
+                    let q = &app.question_answer;
+                    if q.custom_deck.is_some() {
+                        let text = CustomQuestion::from_question_answer(q).to_editable_text();
+                        app.edit_input = TextInput::new(text);
+                        app.screen = Screen::Edit(EditTarget::CustomQuestion);
                     } else {
-                        println!("{}", "Glückwunsch! Du hast alle Fragen gelernt!".green());
-                        app.exit = true;
+                        app.edit_input = TextInput::new(q.to_editable_text());
+                        app.screen = Screen::Edit(EditTarget::Question);
                     }
                 }
-                _ => {}
-            },
-            None => match key_event.code {
-                Char('q') | KeyCode::Esc => app.exit = true,
-                Char('w') | KeyCode::Up => list_move_up(&mut app.item_list_state),
-                Char('s') | KeyCode::Down => list_move_down(
-                    &mut app.item_list_state,
-                    app.question_answer.possible_answers.len(),
-                ),
-                Char('e') | KeyCode::Enter => {
-                    if let Some(i) = app.item_list_state.selected() {
-                        if i < app.question_answer.possible_answers.len() {
-                            app.question_answer.user_answer = Some(i);
-                            app.item_list_state.select(None);
-
-                            if app.question_answer.right_answer == i {
-                                app.total_progress += 1;
-                                app.question_answer.count_correctly_answered += 1;
-                            } else {
-                                app.total_progress -= app.question_answer.count_correctly_answered;
-                                app.question_answer.count_correctly_answered = 0;
-                            }
-
-                            db.update_count_correct_answers(
-                                app.question_answer.id,
-                                app.question_answer.count_correctly_answered,
-                            )?;
-                        }
+                (Screen::Quiz, Char('x')) if shortcut => {
+                    app.edit_input = TextInput::new(&app.question_answer.explanation);
+                    app.screen = Screen::Edit(EditTarget::Explanation);
+                }
+                (Screen::Quiz, KeyCode::Tab) => {
+                    app.screen = Screen::Menu;
+                    app.menu_state.select(Some(0));
+                }
+                (Screen::Quiz, _) => match app.answer_mode {
+                    AnswerMode::Choice => update_choice(key_event, app, db)?,
+                    AnswerMode::FreeRecall => update_free_recall(key_event, app, db)?,
+                },
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles keys in [Screen::Menu].
+fn update_menu(key_event: KeyEvent, app: &mut App, db: &DB) -> Result<()> {
+    let entries = menu::entries(app);
+    match key_event.code {
+        KeyCode::Tab | KeyCode::Esc | Char('q') => app.screen = Screen::Quiz,
+        Char('w') | KeyCode::Up => list_move_up(&mut app.menu_state),
+        Char('s') | KeyCode::Down => list_move_down(&mut app.menu_state, entries.len()),
+        Char('e') | KeyCode::Enter => {
+            if let Some(entry) = app.menu_state.selected().and_then(|i| entries.get(i)) {
+                apply_menu_action(app, db, entry.action.clone())?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Applies the choice of the user in the menu and returns to the quiz.
+///
+/// If the new selection holds no questions, the old one is kept and the user is told so.
+fn apply_menu_action(app: &mut App, db: &DB, action: MenuAction) -> Result<()> {
+    match action {
+        MenuAction::Pool(pool) => {
+            let mut selection = app.selection.clone();
+            selection.pool = pool;
+            match db.next_question(&selection)? {
+                Some(q) => {
+                    app.selection = selection;
+                    show_question(app, q);
+                    app.screen = Screen::Quiz;
+                }
+                None => app.status = Some(format!("{} ist leer.", selection.pool.name())),
+            }
+        }
+        MenuAction::Order(order) => {
+            app.selection.order = order;
+            app.screen = Screen::Quiz;
+        }
+        MenuAction::NewQuestion => {
+            app.edit_input = TextInput::new("");
+            app.screen = Screen::Edit(EditTarget::NewQuestion);
+        }
+    }
+
+    Ok(())
+}
+
+/// Saves the corrected texts of the current question, see [EditTarget::Question].
+fn save_correction(app: &mut App, db: &DB, text: &str) -> Result<()> {
+    let Some(catalog) = db.catalog()? else {
+        app.status =
+            Some("Für Korrekturen bitte den Katalog neu laden (ubilerntui lade).".to_owned());
+        return Ok(());
+    };
+    let parts: Vec<&str> = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    let q = &app.question_answer;
+    if parts.len() != q.possible_answers.len() + 1 {
+        app.status = Some(format!(
+            "Korrektur verworfen: Frage und {} Antworten durch Leerzeilen trennen.",
+            q.possible_answers.len()
+        ));
+        return Ok(());
+    }
+
+    for (part, text) in parts.into_iter().enumerate() {
+        db.set_override(&catalog, q.id, part, text)?;
+    }
+    reload_question(app, db)
+}
+
+/// Saves the edited custom question, see [EditTarget::CustomQuestion]. An empty text deletes it.
+///
+/// Returns `false` if the text is invalid, the user is told why.
+fn save_custom_question(app: &mut App, db: &DB, text: &str) -> Result<bool> {
+    let id = app.question_answer.id;
+    if text.trim().is_empty() {
+        db.delete_custom_question(id)?;
+        app.undo = None;
+        update_totals(app, db)?;
+        next_question(app, db)?;
+        app.status = Some(format!("Eigene Frage {} gelöscht.", custom_number(id)));
+        return Ok(true);
+    }
+
+    let q = match CustomQuestion::parse_editable_text(text) {
+        Ok(q) => q,
+        Err(err) => {
+            app.status = Some(err);
+            return Ok(false);
+        }
+    };
+    if q.right_answers.len() + q.wrong_answers.len() != app.question_answer.possible_answers.len() {
+        // The answers can not be matched with the ones shown anymore.
+        db.update_custom_question(id, &q)?;
+        app.undo = None;
+        if let Some(q) = db.get_question(id)? {
+            show_question(app, q);
+        }
+    } else {
+        db.update_custom_question(id, &q)?;
+        reload_question(app, db)?;
+    }
+    Ok(true)
+}
+
+/// Saves a new custom question, see [EditTarget::NewQuestion]. An empty text discards it.
+///
+/// The question is put into the deck trained right now or into [DEFAULT_DECK].
+/// Returns `false` if the text is invalid, the user is told why.
+fn save_new_question(app: &mut App, db: &DB, text: &str) -> Result<bool> {
+    if text.trim().is_empty() {
+        return Ok(true);
+    }
+    let q = match CustomQuestion::parse_editable_text(text) {
+        Ok(q) => q,
+        Err(err) => {
+            app.status = Some(err);
+            return Ok(false);
+        }
+    };
+    let deck = match &app.selection.pool {
+        Pool::Custom { deck: Some(deck) } => deck.clone(),
+        _ => DEFAULT_DECK.to_owned(),
+    };
+    let id = db.insert_custom_question(&deck, &q)?;
+    update_totals(app, db)?;
+    app.status = Some(format!(
+        "Eigene Frage {} in {deck} gespeichert.",
+        custom_number(id)
+    ));
+    Ok(true)
+}
+
+/// Reloads the texts of the current question, keeping the order of its answers and the answer given so far.
+fn reload_question(app: &mut App, db: &DB) -> Result<()> {
+    let q = &app.question_answer;
+    if let Some(mut reloaded) = db.get_question(q.id)? {
+        reloaded.reorder(&q.order);
+        reloaded.marked = q.marked.clone();
+        reloaded.user_answers = q.user_answers.clone();
+        app.question_answer = reloaded;
+    }
+    Ok(())
+}
+
+/// Recounts the total progress after questions were added or deleted.
+fn update_totals(app: &mut App, db: &DB) -> Result<()> {
+    app.total_progress = db.get_total_progress(&app.selection.filter)?;
+    app.total_question_count = db.get_total_question_count(&app.selection.filter)?;
+    Ok(())
+}
+
+/// Handles keys in [Screen::Edit].
+///
+/// Enter starts a new line, escape saves the text and returns to the quiz, ctrl+q or ctrl+c discards the changes.
+/// Invalid custom questions keep the editor open, an emptied one is only deleted after pressing escape twice.
+fn update_edit(key_event: KeyEvent, app: &mut App, db: &DB, target: EditTarget) -> Result<()> {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    let confirm_delete = std::mem::take(&mut app.confirm_delete);
+    match key_event.code {
+        Char('q') | Char('c') if ctrl => {
+            app.screen = Screen::Quiz;
+            app.status = Some("Änderungen verworfen.".to_owned());
+        }
+        KeyCode::Esc
+            if target == EditTarget::CustomQuestion
+                && app.edit_input.value().trim().is_empty()
+                && !confirm_delete =>
+        {
+            app.confirm_delete = true;
+            app.status = Some(format!(
+                "Eigene Frage {} mit ihrem Verlauf löschen? Zum Löschen erneut esc drücken, zum Verwerfen ctrl+q.",
+                custom_number(app.question_answer.id)
+            ));
+        }
+        KeyCode::Esc => {
+            let text = app.edit_input.value().trim_end().to_owned();
+            let q = &mut app.question_answer;
+            let saved = match target {
+                EditTarget::Note => {
+                    db.set_note(q.id, &text)?;
+                    q.note = text;
+                    true
+                }
+                EditTarget::Explanation => {
+                    db.set_explanation(&db.catalog_key(q.id)?, q.id, &text)?;
+                    q.explanation = text;
+                    true
+                }
+                EditTarget::Question => {
+                    save_correction(app, db, &text)?;
+                    true
+                }
+                EditTarget::CustomQuestion => save_custom_question(app, db, &text)?,
+                EditTarget::NewQuestion => save_new_question(app, db, &text)?,
+            };
+            if saved && app.screen == Screen::Edit(target) {
+                app.screen = Screen::Quiz;
+            }
+        }
+        KeyCode::Enter => app.edit_input.insert('\n'),
+        _ => {
+            app.edit_input.handle_key(&key_event);
+        }
+    }
+
+    Ok(())
+}
+
+/// Handles keys in [Screen::Summary].
+///
+/// The user may go back to learning, unless every question is learned.
+/// Taking back the last answer also returns to learning.
+fn update_summary(key_event: KeyEvent, app: &mut App, db: &DB) -> Result<()> {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    match key_event.code {
+        Char('q') | Char('e') | KeyCode::Esc | KeyCode::Enter => app.exit = true,
+        Char('u') | Char('z') if app.undo.is_some() && (ctrl || key_event.code == Char('u')) => {
+            undo(app, db)?;
+            app.finished = false;
+            app.screen = Screen::Quiz;
+        }
+        Char('z') if !app.finished && !ctrl => app.screen = Screen::Quiz,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Handles keys in [AnswerMode::Choice].
+fn update_choice(key_event: KeyEvent, app: &mut App, db: &DB) -> Result<()> {
+    match app.question_answer.user_answers {
+        Some(_) => match key_event.code {
+            Char('q') | KeyCode::Esc => app.screen = Screen::Summary,
+            Char('e') | KeyCode::Enter => next_question(app, db)?,
+            _ => {}
+        },
+        None => match key_event.code {
+            Char('q') | KeyCode::Esc => app.screen = Screen::Summary,
+            Char('w') | KeyCode::Up => list_move_up(&mut app.item_list_state),
+            Char('s') | KeyCode::Down => list_move_down(
+                &mut app.item_list_state,
+                app.question_answer.possible_answers.len(),
+            ),
+            Char(' ') => {
+                if let Some(i) = app.item_list_state.selected() {
+                    if i < app.question_answer.possible_answers.len() {
+                        app.question_answer.toggle_mark(i);
                     }
                 }
-                _ => {}
-            },
+            }
+            Char('e') | KeyCode::Enter => {
+                let user_answers = if !app.question_answer.marked.is_empty() {
+                    Some(app.question_answer.marked.clone())
+                } else {
+                    app.item_list_state
+                        .selected()
+                        .filter(|&i| i < app.question_answer.possible_answers.len())
+                        .map(|i| vec![i])
+                };
+                if let Some(user_answers) = user_answers {
+                    app.question_answer.user_answers = Some(user_answers);
+                    app.item_list_state.select(None);
+                    score_answer(app, db)?;
+                }
+            }
+            _ => {}
         },
     }
 
     Ok(())
 }
 
+/// Handles keys in [AnswerMode::FreeRecall].
+///
+/// Before the answer is revealed every char is typed into [App::text_input], thus only escape quits.
+fn update_free_recall(key_event: KeyEvent, app: &mut App, db: &DB) -> Result<()> {
+    if app.revealed {
+        let grade = match key_event.code {
+            Char('q') | KeyCode::Esc => {
+                app.screen = Screen::Summary;
+                return Ok(());
+            }
+            Char('1') => Grade::Again,
+            Char('2') => Grade::Hard,
+            Char('3') => Grade::Good,
+            Char('4') => Grade::Easy,
+            _ => return Ok(()),
+        };
+        let new_count = grade.apply(app.question_answer.count_correctly_answered);
+        record_answer(app, db, grade != Grade::Again, new_count)?;
+        next_question(app, db)?;
+    } else {
+        match key_event.code {
+            KeyCode::Esc => app.screen = Screen::Summary,
+            KeyCode::Enter => app.revealed = true,
+            _ => {
+                app.text_input.handle_key(&key_event);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Swaps out the answered question with a random one of the [selection](App::selection) and resets the input state.
+///
+/// If a pool other than [Pool::All] is exhausted, the user is told so and training continues with all questions.
+fn next_question(app: &mut App, db: &DB) -> Result<()> {
+    let mut q = db.next_question(&app.selection)?;
+    if q.is_none() && app.selection.pool != Pool::All {
+        app.status = Some(format!("{} geschafft!", app.selection.pool.name()));
+        app.selection.pool = Pool::All;
+        q = db.next_question(&app.selection)?;
+    }
+
+    match q {
+        Some(q) => show_question(app, q),
+        None => {
+            app.finished = true;
+            app.screen = Screen::Summary;
+        }
+    }
+    Ok(())
+}
+
+/// Shows `q` scrambled and resets the input state.
+fn show_question(app: &mut App, q: QuestionAnswer) {
+    app.item_list_state.select(None);
+    app.text_input.clear();
+    app.revealed = false;
+    app.question_answer = q;
+    app.question_answer.scramble(&mut app.rng);
+}
+
+/// Updates the `question progress` of the answered question in [App] and [DB].
+///
+/// A correct answer increases the progress by one up to [TOTAL_COUNT_TRIES_PER_QUESTION], a wrong one resets it.
+/// With [Scoring::Partial] a [partially correct](Evaluation::PartiallyCorrect) answer keeps the progress as is.
+fn score_answer(app: &mut App, db: &DB) -> Result<()> {
+    let count = app.question_answer.count_correctly_answered;
+    let (correct, new_count) = match (app.question_answer.evaluate(), app.scoring) {
+        (Evaluation::Correct, _) => (true, (count + 1).min(TOTAL_COUNT_TRIES_PER_QUESTION)),
+        (Evaluation::PartiallyCorrect, Scoring::Partial) => (false, count),
+        _ => (false, 0),
+    };
+
+    record_answer(app, db, correct, new_count)
+}
+
+/// Saves the answer in the history and sets the `question progress` of the current question to `new_count`.
+/// Also adjusts the total progress and the statistics of the session.
+///
+/// The state before the answer is kept for [undo].
+fn record_answer(app: &mut App, db: &DB, correct: bool, new_count: usize) -> Result<()> {
+    let mut question_answer = app.question_answer.clone();
+    question_answer.user_answers = None;
+    let undo_stats = app.stats.clone();
+
+    let q = &mut app.question_answer;
+    app.stats
+        .record(q.id, correct, q.count_correctly_answered, new_count);
+    app.total_progress = app.total_progress + new_count - q.count_correctly_answered;
+    q.count_correctly_answered = new_count;
+
+    let history_id = db.record_answer(q.id, correct)?;
+    db.update_count_correct_answers(q.id, new_count)?;
+
+    app.undo = Some(Undo {
+        question_answer,
+        text: app.text_input.value().to_owned(),
+        revealed: app.revealed,
+        stats: undo_stats,
+        history_id,
+        new_count,
+    });
+    Ok(())
+}
+
+/// Takes back the last answer and shows its question again, as it was before answering.
+///
+/// The `question progress`, the total progress, the history and the statistics of the session are restored.
+fn undo(app: &mut App, db: &DB) -> Result<()> {
+    let Some(undo) = app.undo.take() else {
+        app.status = Some("Nichts zum Rückgängigmachen.".to_owned());
+        return Ok(());
+    };
+    let old_count = undo.question_answer.count_correctly_answered;
+    db.undo_answer(undo.history_id, undo.question_answer.id, old_count)?;
+
+    app.total_progress = app.total_progress + old_count - undo.new_count;
+    app.question_answer = undo.question_answer;
+    // Note, bookmark and explanation might have changed since the answer.
+    if let Some(q) = db.get_question(app.question_answer.id)? {
+        app.question_answer.note = q.note;
+        app.question_answer.bookmarked = q.bookmarked;
+        app.question_answer.explanation = q.explanation;
+    }
+    app.text_input = TextInput::new(undo.text);
+    app.revealed = undo.revealed;
+    app.stats = undo.stats;
+    app.item_list_state.select(None);
+    app.status = Some("Letzte Antwort zurückgenommen.".to_owned());
+    Ok(())
+}
+
 /// Updates the [list state](ListState) when user moves cursor up.
 ///
 /// Checks if cursor is visible.
@@ -132,3 +561,71 @@ fn list_move_down(list: &mut ListState, list_size: usize) {
     };
     list.select(Some(next));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::QuestionAnswer;
+    use crate::selection::Progress;
+
+    #[test]
+    fn test_score_mastered_question() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        db.insert(1, "nan", "0", vec!["1", "2", "3"])?;
+        db.update_count_correct_answers(1, TOTAL_COUNT_TRIES_PER_QUESTION)?;
+
+        let mut app = App::new(
+            QuestionAnswer::default(),
+            TOTAL_COUNT_TRIES_PER_QUESTION,
+            TOTAL_COUNT_TRIES_PER_QUESTION,
+        );
+        app.selection.filter.progress = Progress::Mastered;
+        app.question_answer = db.get_question(1)?.unwrap();
+        let right_answer = app.question_answer.right_answers.clone();
+        app.question_answer.user_answers = Some(right_answer);
+        score_answer(&mut app, &db)?;
+
+        assert_eq!(
+            app.question_answer.count_correctly_answered,
+            TOTAL_COUNT_TRIES_PER_QUESTION
+        );
+        assert_eq!(app.total_progress, TOTAL_COUNT_TRIES_PER_QUESTION);
+        assert_eq!(
+            db.get_total_progress(&app.selection.filter)?,
+            TOTAL_COUNT_TRIES_PER_QUESTION
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_discard_and_confirm_delete() -> Result<()> {
+        let db = DB::new_in_memory()?;
+        let q = CustomQuestion::parse_editable_text("1+1?\n\n+ 2\n\n- 3").unwrap();
+        let id = db.insert_custom_question(DEFAULT_DECK, &q)?;
+        let mut app = App::new(
+            db.get_question(id)?.unwrap(),
+            0,
+            TOTAL_COUNT_TRIES_PER_QUESTION,
+        );
+        let key = |code| EventType::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        let ctrl_q = EventType::Key(KeyEvent::new(Char('q'), KeyModifiers::CONTROL));
+
+        // Discarding keeps the question.
+        update(key(Char('k')), &mut app, &db)?;
+        app.edit_input = TextInput::default();
+        update(ctrl_q, &mut app, &db)?;
+        assert_eq!(app.screen, Screen::Quiz);
+        assert!(db.get_question(id)?.is_some());
+
+        // An emptied question is deleted only after escape twice.
+        update(key(Char('k')), &mut app, &db)?;
+        app.edit_input = TextInput::default();
+        update(key(KeyCode::Esc), &mut app, &db)?;
+        assert_eq!(app.screen, Screen::Edit(EditTarget::CustomQuestion));
+        assert!(db.get_question(id)?.is_some());
+        update(key(KeyCode::Esc), &mut app, &db)?;
+        assert!(db.get_question(id)?.is_none());
+
+        Ok(())
+    }
+}